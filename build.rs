@@ -1,17 +1,43 @@
+// std
+use std::env;
 // crates.io
-use vergen_gitcl::{CargoBuilder, Emitter, GitclBuilder};
+use vergen_gitcl::{BuildBuilder, CargoBuilder, Emitter, GitclBuilder, RustcBuilder};
 
 fn main() {
 	let mut emitter = Emitter::default();
 
 	emitter
-		.add_instructions(&CargoBuilder::default().target_triple(true).build().unwrap())
+		.add_instructions(&BuildBuilder::default().build_timestamp(true).build().unwrap())
+		.unwrap()
+		.add_instructions(
+			&CargoBuilder::default().features(true).target_triple(true).build().unwrap(),
+		)
+		.unwrap()
+		.add_instructions(&RustcBuilder::default().semver(true).channel(true).build().unwrap())
 		.unwrap();
 
 	// Disable the git version if installed from [crates.io](https://crates.io).
-	if emitter.add_instructions(&GitclBuilder::default().sha(true).build().unwrap()).is_err() {
+	if emitter
+		.fail_on_error()
+		.add_instructions(
+			&GitclBuilder::default()
+				.sha(true)
+				.dirty(true)
+				.commit_date(true)
+				.branch(true)
+				.build()
+				.unwrap(),
+		)
+		.is_err()
+	{
 		println!("cargo:rustc-env=VERGEN_GIT_SHA=crates.io");
+		println!("cargo:rustc-env=VERGEN_GIT_DIRTY=false");
+		println!("cargo:rustc-env=VERGEN_GIT_COMMIT_DATE=unknown");
+		println!("cargo:rustc-env=VERGEN_GIT_BRANCH=crates.io");
 	}
 
 	emitter.emit().unwrap();
+
+	// Cargo only exposes the base profile, `debug` or `release`, to build scripts.
+	println!("cargo:rustc-env=BUILD_PROFILE={}", env::var("PROFILE").unwrap());
 }
@@ -0,0 +1,254 @@
+// std
+use std::{
+	collections::BTreeMap,
+	ffi::OsString,
+	fmt::{Display, Formatter, Result as FmtResult},
+	path::Path,
+};
+// crates.io
+use clap::Command;
+use serde::{Deserialize, Serialize};
+// self
+use crate::{
+	config::{Config, Overrides},
+	prelude::*,
+};
+
+/// User-defined subcommands of the `[alias]` config table, by name.
+pub type Aliases = BTreeMap<String, Alias>;
+
+/// Arguments an alias expands to, a whitespace separated string or a list.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum Alias {
+	Line(String),
+	Args(Vec<String>),
+}
+impl Alias {
+	pub fn args(&self) -> Vec<String> {
+		match self {
+			Self::Line(line) => line.split_whitespace().map(Into::into).collect(),
+			Self::Args(args) => args.clone(),
+		}
+	}
+}
+impl Display for Alias {
+	fn fmt(&self, f: &mut Formatter) -> FmtResult {
+		f.write_str(&self.args().join(" "))
+	}
+}
+
+/// Aliases of the config file at `path`, for expanding them before the configuration is loaded.
+///
+/// Empty on any error, which loading the configuration will report.
+pub fn preload(path: Option<&Path>) -> Aliases {
+	Config::figment(path, Overrides::default())
+		.and_then(|f| Ok(f.extract_inner::<Aliases>("alias")?))
+		.unwrap_or_default()
+}
+
+/// Replace the alias in subcommand position of `args` with its arguments, recursively.
+///
+/// Built-in subcommands of `cmd` always win over aliases.
+pub fn expand(mut args: Vec<OsString>, aliases: &Aliases, cmd: &Command) -> Result<Vec<OsString>> {
+	let mut chain = Vec::<String>::new();
+
+	while let Some(i) = subcommand_index(&args, cmd) {
+		let Some(name) = args[i].to_str().map(ToOwned::to_owned) else { break };
+
+		if is_builtin(cmd, &name) {
+			break;
+		}
+
+		let Some(alias) = aliases.get(&name) else { break };
+
+		if chain.contains(&name) {
+			chain.push(name);
+
+			bail!("alias `{}` is recursive: {}", chain[0], chain.join(" -> "));
+		}
+
+		let expansion = alias.args();
+
+		if expansion.is_empty() {
+			bail!("alias `{name}` is empty");
+		}
+
+		args.splice(i..=i, expansion.into_iter().map(OsString::from));
+		chain.push(name);
+	}
+
+	Ok(args)
+}
+
+/// Add `aliases` as subcommands of `cmd`, so they show up in the help and the completions.
+pub fn register(mut cmd: Command, aliases: &Aliases) -> Command {
+	for (name, alias) in aliases {
+		if !is_builtin(&cmd, name) {
+			cmd = cmd.subcommand(Command::new(name.clone()).about(format!("Alias for `{alias}`")));
+		}
+	}
+
+	cmd
+}
+
+/// Check that no alias is empty or shadows a built-in subcommand of `cmd`.
+pub fn check(aliases: &Aliases, cmd: &Command) -> Result<()> {
+	for (name, alias) in aliases {
+		if is_builtin(cmd, name) {
+			bail!("alias `{name}` shadows the built-in subcommand `{name}`");
+		}
+		if alias.args().is_empty() {
+			bail!("alias `{name}` is empty");
+		}
+	}
+
+	Ok(())
+}
+
+// `help` is only added by clap when building the command.
+fn is_builtin(cmd: &Command, name: &str) -> bool {
+	name == "help" || cmd.find_subcommand(name).is_some()
+}
+
+// Index of the first positional argument, skipping the options of `cmd` and their values.
+fn subcommand_index(args: &[OsString], cmd: &Command) -> Option<usize> {
+	let takes_value = |long: Option<&str>, short: Option<char>| {
+		cmd.get_arguments().any(|a| {
+			(long.is_some() && a.get_long() == long || short.is_some() && a.get_short() == short)
+				&& a.get_action().takes_values()
+		})
+	};
+	let mut i = 1;
+
+	while i < args.len() {
+		let arg = args[i].to_str()?;
+
+		if arg == "--" {
+			return None;
+		}
+
+		if let Some(long) = arg.strip_prefix("--") {
+			if !long.contains('=') && takes_value(Some(long), None) {
+				i += 1;
+			}
+		} else if let Some(shorts) = arg.strip_prefix('-').filter(|s| !s.is_empty()) {
+			// In `-abc`, the first flag taking a value takes the rest, or the next argument.
+			if let Some((j, c)) = shorts.char_indices().find(|(_, c)| takes_value(None, Some(*c))) {
+				if j + c.len_utf8() == shorts.len() {
+					i += 1;
+				}
+			}
+		} else {
+			return Some(i);
+		}
+
+		i += 1;
+	}
+
+	None
+}
+
+#[cfg(test)]
+mod tests {
+	// crates.io
+	use clap::CommandFactory;
+	// self
+	use super::*;
+	use crate::cli::Cli;
+
+	fn args(line: &str) -> Vec<OsString> {
+		line.split_whitespace().map(Into::into).collect()
+	}
+
+	fn aliases(entries: &[(&str, &str)]) -> Aliases {
+		entries.iter().map(|(name, line)| ((*name).into(), Alias::Line((*line).into()))).collect()
+	}
+
+	#[test]
+	fn expand_should_work() {
+		let aliases = aliases(&[
+			("v", "version"),
+			("show", "config show"),
+			("s", "show --origin"),
+			("version", "config show"),
+			("a", "b"),
+			("b", "c"),
+			("c", "a"),
+			("empty", ""),
+		]);
+		let cmd = Cli::command();
+
+		for (line, expected) in [
+			("app v", "app version"),
+			("app v -x", "app version -x"),
+			("app s", "app config show --origin"),
+			// Built-in subcommands win.
+			("app version", "app version"),
+			("app config show", "app config show"),
+			// Flags taking a value, and their values, come before the alias.
+			("app -c cfg.toml v", "app -c cfg.toml version"),
+			("app --config cfg.toml v", "app --config cfg.toml version"),
+			("app --config=cfg.toml v", "app --config=cfg.toml version"),
+			("app -ccfg.toml v", "app -ccfg.toml version"),
+			("app -vvc cfg.toml v", "app -vvc cfg.toml version"),
+			("app -vv v", "app -vv version"),
+			("app --config v", "app --config v"),
+			// Nothing is expanded after `--`.
+			("app -- v", "app -- v"),
+			("app", "app"),
+			("app unknown v", "app unknown v"),
+		] {
+			assert_eq!(expand(args(line), &aliases, &cmd).unwrap(), args(expected), "{line}");
+		}
+		for (line, expected) in [
+			("app a", "alias `a` is recursive: a -> b -> c -> a"),
+			("app -v b", "alias `b` is recursive: b -> c -> a -> b"),
+			("app empty", "alias `empty` is empty"),
+		] {
+			assert_eq!(
+				expand(args(line), &aliases, &cmd).unwrap_err().to_string(),
+				expected,
+				"{line}"
+			);
+		}
+	}
+
+	#[test]
+	fn subcommand_index_should_work() {
+		let cmd = Cli::command();
+
+		for (line, expected) in [
+			("app", None),
+			("app version", Some(1)),
+			("app -v -q version", Some(3)),
+			("app -o json version", Some(3)),
+			("app -ojson version", Some(2)),
+			("app --output json --color=never version", Some(4)),
+			("app -vo json version", Some(3)),
+			("app --log-filter debug", None),
+			("app -- version", None),
+		] {
+			assert_eq!(subcommand_index(&args(line), &cmd), expected, "{line}");
+		}
+	}
+
+	#[test]
+	fn check_should_reject_shadowing_and_empty_aliases() {
+		let cmd = Cli::command();
+
+		assert!(check(&aliases(&[("v", "version")]), &cmd).is_ok());
+		assert_eq!(
+			check(&aliases(&[("version", "config show")]), &cmd).unwrap_err().to_string(),
+			"alias `version` shadows the built-in subcommand `version`"
+		);
+		assert_eq!(
+			check(&aliases(&[("help", "version")]), &cmd).unwrap_err().to_string(),
+			"alias `help` shadows the built-in subcommand `help`"
+		);
+		assert_eq!(
+			check(&aliases(&[("v", " ")]), &cmd).unwrap_err().to_string(),
+			"alias `v` is empty"
+		);
+	}
+}
@@ -0,0 +1,49 @@
+// std
+use std::fmt::{Display, Formatter, Result as FmtResult};
+// crates.io
+use serde::Serialize;
+
+/// Metadata of the running build, emitted by `build.rs`.
+pub const BUILD_INFO: BuildInfo = BuildInfo {
+	version: env!("CARGO_PKG_VERSION"),
+	git_sha: env!("VERGEN_GIT_SHA"),
+	git_branch: env!("VERGEN_GIT_BRANCH"),
+	git_commit_date: env!("VERGEN_GIT_COMMIT_DATE"),
+	git_dirty: env!("VERGEN_GIT_DIRTY"),
+	build_timestamp: env!("VERGEN_BUILD_TIMESTAMP"),
+	profile: env!("BUILD_PROFILE"),
+	target: env!("VERGEN_CARGO_TARGET_TRIPLE"),
+	features: env!("VERGEN_CARGO_FEATURES"),
+	rustc_version: env!("VERGEN_RUSTC_SEMVER"),
+	rustc_channel: env!("VERGEN_RUSTC_CHANNEL"),
+};
+
+/// Build metadata.
+#[derive(Debug, Serialize)]
+pub struct BuildInfo {
+	pub version: &'static str,
+	pub git_sha: &'static str,
+	pub git_branch: &'static str,
+	pub git_commit_date: &'static str,
+	pub git_dirty: &'static str,
+	pub build_timestamp: &'static str,
+	pub profile: &'static str,
+	pub target: &'static str,
+	pub features: &'static str,
+	pub rustc_version: &'static str,
+	pub rustc_channel: &'static str,
+}
+impl Display for BuildInfo {
+	fn fmt(&self, f: &mut Formatter) -> FmtResult {
+		writeln!(f, "version:         {}", self.version)?;
+		writeln!(f, "git sha:         {}", self.git_sha)?;
+		writeln!(f, "git branch:      {}", self.git_branch)?;
+		writeln!(f, "git commit date: {}", self.git_commit_date)?;
+		writeln!(f, "git dirty:       {}", self.git_dirty)?;
+		writeln!(f, "build timestamp: {}", self.build_timestamp)?;
+		writeln!(f, "profile:         {}", self.profile)?;
+		writeln!(f, "target:          {}", self.target)?;
+		writeln!(f, "features:        {}", self.features)?;
+		write!(f, "rustc:           {} ({})", self.rustc_version, self.rustc_channel)
+	}
+}
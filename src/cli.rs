@@ -1,14 +1,71 @@
+// Items of `Run` impls, with `fn` turned into `async fn` by the `async` feature.
+#[cfg(not(feature = "async"))]
+macro_rules! maybe_async {
+	($($item:tt)*) => { $($item)* };
+}
+#[cfg(feature = "async")]
+macro_rules! maybe_async {
+	(fn $($rest:tt)*) => { async fn $($rest)* };
+}
+
+// Awaits `future` with the `async` feature, for calls into `maybe_async!` functions.
+#[cfg(not(feature = "async"))]
+macro_rules! maybe_await {
+	($future:expr) => {
+		$future
+	};
+}
+#[cfg(feature = "async")]
+macro_rules! maybe_await {
+	($future:expr) => {
+		$future.await
+	};
+}
+
+mod completions;
+use completions::CompletionsCmd;
+
+mod config;
+use config::ConfigCmd;
+
+mod generate_docs;
+use generate_docs::GenerateDocsCmd;
+
+mod plugins;
+use plugins::PluginsCmd;
+
+mod self_update;
+use self_update::SelfUpdateCmd;
+
+mod shell;
+use shell::ShellCmd;
+
+mod version;
+use version::VersionCmd;
+
+// std
+use std::{
+	ffi::OsString,
+	path::{Path, PathBuf},
+};
 // crates.io
 use clap::{
-	builder::{
-		styling::{AnsiColor, Effects},
-		Styles,
-	},
-	Parser,
+	error::ErrorKind, ArgAction, Args, ColorChoice, CommandFactory, FromArgMatches, Parser,
+	Subcommand, ValueEnum, ValueHint,
 };
-use tracing_subscriber::{reload::Handle, EnvFilter, Registry};
 // self
-use crate::prelude::*;
+#[cfg(feature = "async")] use crate::runtime::RuntimeFlavor;
+use crate::{
+	alias,
+	config::{Config, Overrides},
+	context::{AppContext, AppDirs},
+	error::ErrorFormat,
+	log::{self, LogFormat},
+	output::OutputFormat,
+	plugin::Plugin,
+	prelude::*,
+	theme::Theme,
+};
 
 /// Cli.
 #[derive(Debug, Parser)]
@@ -22,25 +79,301 @@ use crate::prelude::*;
 	),
 	about,
 	rename_all = "kebab",
-	styles = styles(),
+	arg_required_else_help = true,
 )]
 pub struct Cli {
-	/// Placeholder.
-	#[arg(long, short, value_name = "NUM", default_value_t = String::from("Welcome to use rust-initializer!"))]
-	placeholder: String,
+	#[command(flatten)]
+	global: GlobalArgs,
+	#[command(subcommand)]
+	command: Command,
 }
 impl Cli {
-	pub fn run(&self, _log_filter_handle: Handle<EnvFilter, Registry>) -> Result<()> {
-		dbg!(self);
+	maybe_async! {
+		fn dispatch(&self, cx: &AppContext) -> Result<()> {
+			tracing::debug!(config = ?cx.config, "effective configuration");
+
+			// A signal may have arrived while loading the configuration.
+			if cx.shutdown.is_requested() {
+				return Err(eyre!("shutdown requested")).category(Category::Interrupted);
+			}
+
+			maybe_await!(self.command.run(cx, &self.global))
+		}
+	}
+
+	/// Parse `args` after expanding the aliases of the config file, rendering help and usage
+	/// errors with `color`, see [`color_arg`], and the theme of the config file.
+	pub fn try_parse_styled(args: Vec<OsString>, color: ColorChoice) -> Result<Self, clap::Error> {
+		let path = find_arg(&args, "--config", Some("-c")).map(Path::new);
+		let theme = Theme::preload(path);
+		let aliases = alias::preload(path);
+		let cmd = Self::command();
+		let args = alias::expand(args, &aliases, &cmd);
+		let mut cmd = alias::register(cmd, &aliases).color(color).styles(theme.styles());
+		let args = args.map_err(|e| cmd.error(ErrorKind::InvalidSubcommand, e))?;
+		let mut matches = cmd.try_get_matches_from_mut(args.clone())?;
+		let cli = Self::from_arg_matches_mut(&mut matches).map_err(|e| e.format(&mut cmd))?;
+
+		// Without a plugin providing it, an unknown subcommand gets clap's error and suggestions.
+		if let Command::External(external) = &cli.command {
+			let name = external[0].to_string_lossy();
+
+			if !AppDirs::resolve().is_ok_and(|d| Plugin::find(&name, &d.data).is_some()) {
+				cmd.allow_external_subcommands(false).try_get_matches_from(args)?;
+			}
+		}
+
+		Ok(cli)
+	}
+
+	pub fn color(&self) -> ColorChoice {
+		self.global.color
+	}
+
+	pub fn error_format(&self) -> ErrorFormat {
+		self.global.error_format
+	}
+
+	pub fn output_format(&self) -> OutputFormat {
+		self.global.output
+	}
+
+	/// Load the configuration, with the flags of this invocation as the top layer.
+	pub fn load_config(&self) -> Result<Config> {
+		let config = Config::load(self.global.config.as_deref(), self.global.overrides());
+
+		match &self.command {
+			// Inspecting or fixing a broken config must not require a valid one.
+			Command::Config(_) => Ok(config.unwrap_or_default()),
+			_ => config,
+		}
+	}
 
-		Ok(())
+	/// Whether to print a notice about newer releases afterwards, which `self-update` covers
+	/// already.
+	pub fn notifies_updates(&self) -> bool {
+		!matches!(self.command, Command::SelfUpdate(_))
 	}
+
+	/// Long-running commands should poll [`AppContext::shutdown`] and return once it is requested.
+	#[cfg(not(feature = "async"))]
+	pub fn run(&self, cx: &AppContext) -> Result<()> {
+		self.dispatch(cx)
+	}
+
+	/// Driven by the runtime built from [`Config::runtime`], so commands can `.await`.
+	///
+	/// Once a shutdown is requested the command future is dropped at its next `.await`.
+	#[cfg(feature = "async")]
+	pub async fn run(&self, cx: &AppContext) -> Result<()> {
+		tokio::select! {
+			r = self.dispatch(cx) => r,
+			() = cx.shutdown.cancelled() => Err(eyre!("shutdown requested")).category(Category::Interrupted),
+		}
+	}
+}
+
+/// Flags accepted by every subcommand.
+#[derive(Debug, Args)]
+pub struct GlobalArgs {
+	/// Config file to use instead of the one in the config directory.
+	#[arg(long, short, global = true, value_name = "PATH", value_hint = ValueHint::FilePath)]
+	pub config: Option<PathBuf>,
+	/// Log filter directives, overriding `RUST_LOG`, e.g. `info,my_crate=debug`.
+	#[arg(long, global = true, value_name = "DIRECTIVES")]
+	log_filter: Option<String>,
+	/// Log output format, for both the log file and the console.
+	#[arg(long, global = true, value_name = "FORMAT", value_enum)]
+	log_format: Option<LogFormat>,
+	/// Directory to write the log files into.
+	#[arg(long, global = true, value_name = "DIR", value_hint = ValueHint::DirPath)]
+	log_dir: Option<PathBuf>,
+	/// Do not write log files, e.g. for ephemeral runs such as CI.
+	#[arg(long, global = true)]
+	no_log_file: bool,
+	/// Increase the log verbosity, can be repeated; `version` also prints all build metadata.
+	#[arg(long, short, global = true, action = ArgAction::Count)]
+	pub verbose: u8,
+	/// Decrease the log verbosity, can be repeated.
+	#[arg(long, short, global = true, action = ArgAction::Count)]
+	quiet: u8,
+	/// When to use colors, `auto` honors `NO_COLOR`, `CLICOLOR_FORCE` and whether the output is a
+	/// terminal.
+	#[arg(long, global = true, value_name = "WHEN", value_enum, default_value_t)]
+	pub color: ColorChoice,
+	/// How to print command results.
+	#[arg(long, short, global = true, value_name = "FORMAT", value_enum, default_value_t)]
+	output: OutputFormat,
+	/// How to print errors.
+	#[arg(long, global = true, value_name = "FORMAT", value_enum, default_value_t)]
+	error_format: ErrorFormat,
+	/// Async runtime scheduler.
+	#[cfg(feature = "async")]
+	#[arg(long, global = true, value_name = "FLAVOR", value_enum)]
+	runtime: Option<RuntimeFlavor>,
+	/// Worker threads of the multi-thread runtime, one per CPU core by default.
+	#[cfg(feature = "async")]
+	#[arg(long, global = true, value_name = "NUM")]
+	worker_threads: Option<usize>,
+}
+impl GlobalArgs {
+	/// Configuration layer holding the values set by these flags.
+	pub fn overrides(&self) -> Overrides {
+		let mut overrides = Overrides::default();
+
+		if self.verbose != 0 || self.quiet != 0 {
+			overrides.set("log.filter", log::verbosity(self.verbose, self.quiet).to_string());
+		}
+		if let Some(directives) = &self.log_filter {
+			overrides.set("log.filter", directives);
+		}
+		if let Some(format) = self.log_format {
+			overrides.set("log.file.format", format);
+			overrides.set("log.console.format", format);
+		}
+		if let Some(dir) = &self.log_dir {
+			overrides.set("log.file.dir", dir);
+		}
+		if self.no_log_file {
+			overrides.set("log.file.enabled", false);
+		}
+		#[cfg(feature = "async")]
+		if let Some(flavor) = self.runtime {
+			overrides.set("runtime.flavor", flavor);
+		}
+		#[cfg(feature = "async")]
+		if let Some(n) = self.worker_threads {
+			overrides.set("runtime.worker_threads", n);
+		}
+
+		overrides
+	}
+}
+
+/// A subcommand.
+///
+/// Add one by creating a `cli/<name>.rs` module holding an `Args` struct implementing this trait,
+/// then listing it in [`Command`].
+///
+/// `run` is an `async fn` with the `async` feature. Implementations not awaiting anything can
+/// wrap it in `maybe_async!` to build either way.
+pub trait Run {
+	/// `global` holds the flags of this invocation, `cx` the state shared by all of them.
+	#[cfg(not(feature = "async"))]
+	fn run(&self, cx: &AppContext, global: &GlobalArgs) -> Result<()>;
+
+	/// `global` holds the flags of this invocation, `cx` the state shared by all of them.
+	#[cfg(feature = "async")]
+	async fn run(&self, cx: &AppContext, global: &GlobalArgs) -> Result<()>;
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+	Completions(CompletionsCmd),
+	Config(ConfigCmd),
+	#[command(hide = true)]
+	GenerateDocs(GenerateDocsCmd),
+	Plugins(PluginsCmd),
+	SelfUpdate(SelfUpdateCmd),
+	Shell(ShellCmd),
+	Version(VersionCmd),
+	/// Any other subcommand, run by its plugin.
+	#[command(external_subcommand)]
+	External(Vec<OsString>),
+}
+impl Run for Command {
+	maybe_async! {
+		fn run(&self, cx: &AppContext, global: &GlobalArgs) -> Result<()> {
+			match self {
+				Self::Completions(cmd) => maybe_await!(cmd.run(cx, global)),
+				Self::Config(cmd) => maybe_await!(cmd.run(cx, global)),
+				Self::GenerateDocs(cmd) => maybe_await!(cmd.run(cx, global)),
+				Self::Plugins(cmd) => maybe_await!(cmd.run(cx, global)),
+				Self::SelfUpdate(cmd) => maybe_await!(cmd.run(cx, global)),
+				Self::Shell(cmd) => maybe_await!(cmd.run(cx, global)),
+				Self::Version(cmd) => maybe_await!(cmd.run(cx, global)),
+				Self::External(args) => {
+					let name = args[0].to_string_lossy();
+
+					Plugin::find(&name, &cx.dirs.data)
+						.ok_or_else(|| {
+							eyre!("unrecognized subcommand `{name}`, and no plugin provides it")
+						})
+						.category(Category::Usage)?
+						.exec(cx, global, &args[1..])
+				},
+			}
+		}
+	}
+}
+
+/// `--color` choice of `args`, for the output produced before they are parsed.
+pub fn color_arg(args: &[OsString]) -> ColorChoice {
+	find_arg(args, "--color", None)
+		.and_then(|v| ColorChoice::from_str(v, true).ok())
+		.unwrap_or_default()
+}
+
+// Clap renders help and usage errors while parsing, so the flags styling them have to be found
+// beforehand. Returns the value of the last occurrence of `long`, or of `short`.
+fn find_arg<'a>(args: &'a [OsString], long: &str, short: Option<&str>) -> Option<&'a str> {
+	let mut args = args.iter().skip(1).take_while(|a| *a != "--").map(|a| a.to_str());
+	let mut found = None;
+
+	while let Some(arg) = args.next() {
+		let Some(arg) = arg else { continue };
+
+		if arg == long || Some(arg) == short {
+			found = args.next().flatten().or(found);
+		} else if let Some(v) = arg.strip_prefix(long).and_then(|a| a.strip_prefix('=')) {
+			found = Some(v);
+		}
+	}
+
+	found
 }
 
-fn styles() -> Styles {
-	Styles::styled()
-		.header(AnsiColor::Red.on_default() | Effects::BOLD)
-		.usage(AnsiColor::Red.on_default() | Effects::BOLD)
-		.literal(AnsiColor::Blue.on_default() | Effects::BOLD)
-		.placeholder(AnsiColor::Green.on_default())
+#[cfg(test)]
+mod tests {
+	// self
+	use super::*;
+
+	fn args(line: &str) -> Vec<OsString> {
+		line.split_whitespace().map(Into::into).collect()
+	}
+
+	#[test]
+	fn find_arg_should_work() {
+		for (line, expected) in [
+			("app", None),
+			("app --config a.toml", Some("a.toml")),
+			("app -c a.toml", Some("a.toml")),
+			("app --config=a.toml", Some("a.toml")),
+			("app version --config a.toml", Some("a.toml")),
+			// The last occurrence wins.
+			("app -c a.toml --config=b.toml", Some("b.toml")),
+			("app --config=a.toml -c b.toml", Some("b.toml")),
+			// A missing value keeps the previous one.
+			("app -c a.toml --config", Some("a.toml")),
+			("app --config-dir a", None),
+			("app --configx=a", None),
+			// Arguments after `--` are not options.
+			("app -- --config a.toml", None),
+			("app -c a.toml -- -c b.toml", Some("a.toml")),
+		] {
+			assert_eq!(find_arg(&args(line), "--config", Some("-c")), expected, "{line}");
+		}
+	}
+
+	#[test]
+	fn color_arg_should_work() {
+		for (line, expected) in [
+			("app", ColorChoice::Auto),
+			("app --color never", ColorChoice::Never),
+			("app --color=ALWAYS", ColorChoice::Always),
+			("app --color rainbow", ColorChoice::Auto),
+		] {
+			assert_eq!(color_arg(&args(line)), expected, "{line}");
+		}
+	}
 }
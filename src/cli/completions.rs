@@ -0,0 +1,92 @@
+// std
+use std::{env, fs, path::PathBuf};
+// crates.io
+use clap::{Args, CommandFactory};
+use clap_complete::Shell;
+// self
+use crate::{
+	alias,
+	cli::{Cli, GlobalArgs, Run},
+	context::AppContext,
+	output,
+	prelude::*,
+};
+
+/// Generate shell completions.
+///
+/// Scripts are derived from the `Cli` definition, so new subcommands and arguments are picked up
+/// automatically, along with the aliases of the configuration. Give arguments a `value_hint` or a
+/// `ValueEnum` type to complete their values.
+#[derive(Debug, Args)]
+pub struct CompletionsCmd {
+	/// Target shell.
+	#[arg(value_enum)]
+	shell: Shell,
+	/// Install the script into the conventional per-user location instead of printing it.
+	#[arg(long)]
+	install: bool,
+}
+impl Run for CompletionsCmd {
+	maybe_async! {
+		fn run(&self, cx: &AppContext, _: &GlobalArgs) -> Result<()> {
+			let bin = env!("CARGO_PKG_NAME");
+			let mut cmd = alias::register(Cli::command(), &cx.config.alias);
+			let mut script = Vec::new();
+
+			clap_complete::generate(self.shell, &mut cmd, bin, &mut script);
+
+			if !self.install {
+				return output::stdout(&script);
+			}
+
+			let (path, hint) = install_path(self.shell, bin)?;
+
+			if let Some(dir) = path.parent() {
+				fs::create_dir_all(dir)?;
+			}
+
+			fs::write(&path, script)
+				.wrap_err_with(|| format!("failed to write `{}`", path.display()))?;
+			cx.out.status(format_args!("installed `{}`", path.display()));
+
+			if let Some(hint) = hint {
+				cx.out.status(hint);
+			}
+
+			Ok(())
+		}
+	}
+}
+
+fn install_path(shell: Shell, bin: &str) -> Result<(PathBuf, Option<String>)> {
+	let home = env::home_dir().context("failed to locate the home directory")?;
+	let xdg = |var, default| {
+		env::var_os(var).filter(|v| !v.is_empty()).map(PathBuf::from).unwrap_or(home.join(default))
+	};
+
+	Ok(match shell {
+		Shell::Bash => (
+			xdg("XDG_DATA_HOME", ".local/share").join("bash-completion/completions").join(bin),
+			None,
+		),
+		Shell::Elvish => (
+			xdg("XDG_CONFIG_HOME", ".config").join("elvish/lib").join(format!("{bin}.elv")),
+			Some(format!("add `use {bin}` to your `rc.elv` to enable it")),
+		),
+		Shell::Fish => (
+			xdg("XDG_CONFIG_HOME", ".config").join("fish/completions").join(format!("{bin}.fish")),
+			None,
+		),
+		Shell::PowerShell => {
+			let path = home.join("Documents/PowerShell/Completions").join(format!("{bin}.ps1"));
+			let hint = format!("add `. \"{}\"` to your `$PROFILE` to enable it", path.display());
+
+			(path, Some(hint))
+		},
+		Shell::Zsh => (
+			home.join(".zfunc").join(format!("_{bin}")),
+			Some("make sure `~/.zfunc` is in your `fpath` before `compinit` runs".into()),
+		),
+		_ => bail!("installing completions for `{shell}` is not supported"),
+	})
+}
@@ -0,0 +1,199 @@
+// std
+use std::{
+	env,
+	fmt::{Display, Formatter, Result as FmtResult},
+	fs,
+	path::{Path, PathBuf},
+	process,
+};
+// crates.io
+use clap::{Args, Subcommand};
+use figment::{
+	value::{Dict, Value},
+	Figment, Metadata, Source,
+};
+use serde::Serialize;
+// self
+use crate::{
+	cli::{GlobalArgs, Run},
+	config::{self, Config, Overrides},
+	context::AppContext,
+	output::{Output, Tabular},
+	prelude::*,
+};
+
+/// Inspect and manage the configuration.
+#[derive(Debug, Args)]
+pub struct ConfigCmd {
+	#[command(subcommand)]
+	action: ConfigAction,
+}
+impl Run for ConfigCmd {
+	maybe_async! {
+		fn run(&self, cx: &AppContext, global: &GlobalArgs) -> Result<()> {
+			let path = global.config.as_deref();
+			let overrides = global.overrides();
+
+			match &self.action {
+				ConfigAction::Show => show(cx.out, &Config::figment(path, overrides)?),
+				ConfigAction::Path => cx.out.value(&config::path(path)?.display().to_string()),
+				ConfigAction::Validate => validate(cx.out, path, overrides),
+				ConfigAction::Init { force } => init(cx.out, &config::path(path)?, *force),
+				ConfigAction::Edit => edit(cx, path, overrides),
+			}
+		}
+	}
+}
+
+#[derive(Debug, Subcommand)]
+enum ConfigAction {
+	/// Print the effective configuration, annotating where each value comes from.
+	Show,
+	/// Print the path of the config file.
+	Path,
+	/// Check that the configuration loads and holds valid values.
+	Validate,
+	/// Write a commented default config file.
+	Init {
+		/// Overwrite an existing file.
+		#[arg(long)]
+		force: bool,
+	},
+	/// Open the config file in `$VISUAL` or `$EDITOR`, creating it if needed.
+	Edit,
+}
+
+/// A configuration value and where it comes from.
+#[derive(Debug, Serialize)]
+struct Entry {
+	key: String,
+	/// `null` if unset.
+	value: serde_json::Value,
+	source: String,
+}
+impl Tabular for Entry {
+	const HEADER: &'static [&'static str] = &["key", "value", "source"];
+
+	fn row(&self) -> Vec<String> {
+		let value = if self.value.is_null() { "<unset>".into() } else { self.value.to_string() };
+
+		vec![self.key.clone(), value, self.source.clone()]
+	}
+}
+
+/// Outcome of `config validate`.
+#[derive(Debug, Serialize)]
+struct Validated {
+	/// Config file checked, `None` if only the defaults are in use.
+	file: Option<PathBuf>,
+}
+impl Display for Validated {
+	fn fmt(&self, f: &mut Formatter) -> FmtResult {
+		match &self.file {
+			Some(p) => write!(f, "`{}` is valid", p.display()),
+			None => f.write_str("no config file found, the defaults are in use"),
+		}
+	}
+}
+
+fn show(out: Output, figment: &Figment) -> Result<()> {
+	let dict = figment.extract::<Dict>()?;
+	let mut values = Vec::new();
+
+	flatten(String::new(), &dict, &mut values);
+
+	let entries = values
+		.into_iter()
+		.map(|(key, value)| {
+			Ok(Entry {
+				value: match value {
+					Value::Empty(..) => serde_json::Value::Null,
+					v => serde_json::to_value(v)?,
+				},
+				source: figment.find_metadata(&key).map(describe).unwrap_or_default(),
+				key,
+			})
+		})
+		.collect::<Result<Vec<_>>>()?;
+
+	out.list(&entries)
+}
+
+fn flatten<'a>(prefix: String, dict: &'a Dict, entries: &mut Vec<(String, &'a Value)>) {
+	for (k, v) in dict {
+		let key = if prefix.is_empty() { k.to_owned() } else { format!("{prefix}.{k}") };
+
+		match v {
+			Value::Dict(_, d) => flatten(key, d, entries),
+			v => entries.push((key, v)),
+		}
+	}
+}
+
+fn describe(metadata: &Metadata) -> String {
+	match &metadata.source {
+		Some(Source::File(p)) => format!("{} `{}`", metadata.name, p.display()),
+		_ => metadata.name.to_string(),
+	}
+}
+
+fn validate(out: Output, path: Option<&Path>, overrides: Overrides) -> Result<()> {
+	Config::load(path, overrides)?;
+
+	out.value(&Validated { file: config::resolve_path(path)? })
+}
+
+fn init(out: Output, path: &Path, force: bool) -> Result<()> {
+	if path.extension().is_some_and(|e| e != "toml") {
+		bail!("only TOML config files can be initialized, got `{}`", path.display());
+	}
+	if path.exists() && !force {
+		bail!("`{}` already exists, pass `--force` to overwrite it", path.display());
+	}
+	if let Some(dir) = path.parent() {
+		fs::create_dir_all(dir)?;
+	}
+
+	fs::write(path, config::TEMPLATE)
+		.wrap_err_with(|| format!("failed to write `{}`", path.display()))?;
+	out.status(format_args!("wrote `{}`", path.display()));
+
+	Ok(())
+}
+
+fn edit(cx: &AppContext, path: Option<&Path>, overrides: Overrides) -> Result<()> {
+	let file = config::path(path)?;
+
+	if !cx.term.is_interactive() {
+		return Err(eyre!(
+			"`config edit` needs an interactive terminal, edit `{}` directly",
+			file.display()
+		))
+		.category(Category::Usage);
+	}
+
+	if !file.exists() {
+		init(cx.out, &file, false)?;
+	}
+
+	let editor = editor();
+	let status = process::Command::new(&editor)
+		.arg(&file)
+		.status()
+		.wrap_err_with(|| format!("failed to launch `{}`", editor.display()))?;
+
+	if !status.success() {
+		bail!("`{}` exited with {status}", editor.display());
+	}
+
+	// Report mistakes right away rather than on the next run.
+	validate(cx.out, Some(&file), overrides)
+}
+
+fn editor() -> PathBuf {
+	env::var_os("VISUAL")
+		.or_else(|| env::var_os("EDITOR"))
+		.filter(|e| !e.is_empty())
+		.map(PathBuf::from)
+		.unwrap_or_else(|| if cfg!(windows) { "notepad" } else { "vi" }.into())
+}
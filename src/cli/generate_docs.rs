@@ -0,0 +1,49 @@
+// std
+use std::{fs, path::PathBuf};
+// crates.io
+use clap::{Args, CommandFactory, ValueHint};
+use clap_markdown::MarkdownOptions;
+// self
+use crate::{
+	cli::{Cli, GlobalArgs, Run},
+	context::AppContext,
+	prelude::*,
+};
+
+/// Generate man pages and a markdown CLI reference.
+///
+/// Writes `man/<NAME>*.1`, one page per visible subcommand, and `<NAME>.md` into the output
+/// directory. Used by the release workflow to ship the docs next to the binary.
+#[derive(Debug, Args)]
+pub struct GenerateDocsCmd {
+	/// Directory to write the docs into.
+	#[arg(long, value_name = "DIR", default_value = ".", value_hint = ValueHint::DirPath)]
+	out_dir: PathBuf,
+}
+impl Run for GenerateDocsCmd {
+	maybe_async! {
+		fn run(&self, cx: &AppContext, _: &GlobalArgs) -> Result<()> {
+			let bin = env!("CARGO_PKG_NAME");
+			let cmd = Cli::command().name(bin);
+			let man_dir = self.out_dir.join("man");
+
+			fs::create_dir_all(&man_dir)?;
+			clap_mangen::generate_to(cmd.clone(), &man_dir)
+				.wrap_err_with(|| format!("failed to write man pages into `{}`", man_dir.display()))?;
+
+			let markdown = clap_markdown::help_markdown_command_custom(
+				&cmd,
+				&MarkdownOptions::new()
+					.title(format!("`{bin}` Command Line Reference"))
+					.show_footer(false),
+			);
+			let markdown_path = self.out_dir.join(format!("{bin}.md"));
+
+			fs::write(&markdown_path, markdown)
+				.wrap_err_with(|| format!("failed to write `{}`", markdown_path.display()))?;
+			cx.out.status(format_args!("wrote docs into `{}`", self.out_dir.display()));
+
+			Ok(())
+		}
+	}
+}
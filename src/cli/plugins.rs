@@ -0,0 +1,67 @@
+// crates.io
+use clap::{Args, Subcommand};
+use serde::Serialize;
+// self
+use crate::{
+	cli::{GlobalArgs, Run},
+	context::AppContext,
+	output::Tabular,
+	plugin,
+	prelude::*,
+};
+
+/// Inspect the plugins providing extra subcommands.
+///
+/// An unknown subcommand `<SUBCOMMAND>` runs the executable `<NAME>-<SUBCOMMAND>` found in the
+/// plugins directory of the data directory, or on `PATH`.
+#[derive(Debug, Args)]
+pub struct PluginsCmd {
+	#[command(subcommand)]
+	action: PluginsAction,
+}
+impl Run for PluginsCmd {
+	maybe_async! {
+		fn run(&self, cx: &AppContext, _: &GlobalArgs) -> Result<()> {
+			match self.action {
+				PluginsAction::List => {
+					let plugins = plugin::discover(&cx.dirs.data)
+						.into_iter()
+						.map(|p| Entry {
+							version: p.version(),
+							path: p.path.display().to_string(),
+							name: p.name,
+						})
+						.collect::<Vec<_>>();
+
+					cx.out.list(&plugins)
+				},
+			}
+		}
+	}
+}
+
+#[derive(Debug, Subcommand)]
+enum PluginsAction {
+	/// List the discovered plugins and their versions.
+	List,
+}
+
+/// A discovered plugin.
+#[derive(Debug, Serialize)]
+struct Entry {
+	name: String,
+	/// `null` if the plugin does not support `--version`.
+	version: Option<String>,
+	path: String,
+}
+impl Tabular for Entry {
+	const HEADER: &'static [&'static str] = &["name", "version", "path"];
+
+	fn row(&self) -> Vec<String> {
+		vec![
+			self.name.clone(),
+			self.version.clone().unwrap_or_else(|| "<unknown>".into()),
+			self.path.clone(),
+		]
+	}
+}
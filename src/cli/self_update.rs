@@ -0,0 +1,83 @@
+// std
+use std::{fmt, time::Duration};
+// crates.io
+use clap::Args;
+use semver::Version;
+use serde::Serialize;
+// self
+use crate::{
+	cli::{GlobalArgs, Run},
+	context::AppContext,
+	prelude::*,
+	update::{self, Source},
+};
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Update this executable to the latest release.
+///
+/// The release archive is verified against the release `SHA256` manifest, and the new executable
+/// must run before it replaces the current one.
+#[derive(Debug, Args)]
+pub struct SelfUpdateCmd {
+	/// Only report whether an update is available.
+	#[arg(long)]
+	check: bool,
+	/// Install this release instead of the latest one, e.g. `v1.2.3`.
+	#[arg(long, value_name = "TAG")]
+	tag: Option<String>,
+	/// Install even if the release is not newer, e.g. to downgrade or repair.
+	#[arg(long)]
+	force: bool,
+	/// Override `update.source`: `github`, or a directory or URL mirroring the releases.
+	#[arg(long, value_name = "SOURCE")]
+	source: Option<String>,
+}
+impl Run for SelfUpdateCmd {
+	maybe_async! {
+		fn run(&self, cx: &AppContext, _: &GlobalArgs) -> Result<()> {
+			let source = Source::from(self.source.as_deref().unwrap_or(&cx.config.update.source));
+			let tag = match &self.tag {
+				Some(tag) => tag.to_owned(),
+				None => source.latest_tag(TIMEOUT)?,
+			};
+			let current = Version::parse(env!("CARGO_PKG_VERSION"))?;
+			let release = update::version(&tag)?;
+
+			if self.check {
+				return cx.out.value(&Check { update_available: release > current, current, release });
+			}
+			if release <= current && !self.force {
+				cx.out
+					.status(format_args!("already up to date, {current} is not older than {release}"));
+
+				return Ok(());
+			}
+
+			cx.out.status(format_args!("downloading `{tag}`"));
+
+			let (name, archive) = update::download(&source, &tag, TIMEOUT)?;
+			let exe = update::replace_exe(&update::extract(&name, &archive)?)?;
+
+			cx.out.status(format_args!("updated `{}` from {current} to {release}", exe.display()));
+
+			Ok(())
+		}
+	}
+}
+
+#[derive(Debug, Serialize)]
+struct Check {
+	current: Version,
+	release: Version,
+	update_available: bool,
+}
+impl fmt::Display for Check {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		if self.update_available {
+			write!(f, "update available: {} -> {}", self.current, self.release)
+		} else {
+			write!(f, "up to date: {}", self.current)
+		}
+	}
+}
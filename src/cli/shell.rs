@@ -0,0 +1,220 @@
+// std
+use std::{ffi::OsString, iter};
+// crates.io
+use clap::{Args, CommandFactory};
+use rustyline::{
+	completion::{Completer, Pair},
+	error::ReadlineError,
+	highlight::Highlighter,
+	hint::Hinter,
+	history::DefaultHistory,
+	validate::Validator,
+	Context, Editor, Helper,
+};
+// self
+use crate::{
+	alias,
+	cli::{self, Cli, Command, GlobalArgs, Run},
+	context::AppContext,
+	error,
+	output::Output,
+	prelude::*,
+	theme::Theme,
+};
+
+const HISTORY_FILE: &str = "shell-history";
+
+/// Open an interactive shell running one command per line.
+///
+/// Lines take the same arguments as the command line, without the executable name; `exit` or
+/// `Ctrl-D` leaves. Logging stays as set up for the shell itself.
+#[derive(Debug, Args)]
+pub struct ShellCmd;
+impl Run for ShellCmd {
+	maybe_async! {
+		fn run(&self, cx: &AppContext, global: &GlobalArgs) -> Result<()> {
+			let history = cx.dirs.data.join(HISTORY_FILE);
+			let mut cmd = alias::register(Cli::command(), &cx.config.alias);
+
+			// Propagate the global arguments to the subcommands.
+			cmd.build();
+
+			let mut editor = Editor::<ShellHelper, DefaultHistory>::new()?;
+
+			editor.set_helper(Some(ShellHelper(cmd)));
+
+			// Missing on first use.
+			let _ = editor.load_history(&history);
+
+			let prompt = format!("{}> ", env!("CARGO_PKG_NAME"));
+
+			while !cx.shutdown.is_requested() {
+				let line = match editor.readline(&prompt) {
+					Ok(line) => line,
+					// `Ctrl-C` only discards the line being edited.
+					Err(ReadlineError::Interrupted) => continue,
+					Err(ReadlineError::Eof) => break,
+					Err(e) => Err(e)?,
+				};
+				let line = line.trim();
+
+				if line.is_empty() {
+					continue;
+				}
+
+				editor.add_history_entry(line)?;
+
+				if let Err(e) = editor.save_history(&history) {
+					tracing::warn!("failed to save the shell history: {e}");
+				}
+				if line == "exit" || line == "quit" {
+					break;
+				}
+
+				match shlex::split(line) {
+					Some(words) => maybe_await!(execute(cx, global, words)),
+					None => eprintln!("unbalanced quotes"),
+				}
+			}
+
+			Ok(())
+		}
+	}
+}
+
+// Parse and run a line, reporting errors without leaving the shell.
+maybe_async! {
+	fn execute(cx: &AppContext, global: &GlobalArgs, words: Vec<String>) {
+		let mut args = iter::once(env!("CARGO_PKG_NAME").into())
+			.chain(words.into_iter().map(OsString::from))
+			.collect::<Vec<_>>();
+
+		// The config file of the shell applies to its lines, unless they pick another one.
+		if let Some(path) = global.config.clone() {
+			if cli::find_arg(&args, "--config", Some("-c")).is_none() {
+				args.splice(1..1, ["--config".into(), path.into_os_string()]);
+			}
+		}
+
+		let line = match Cli::try_parse_styled(args, global.color) {
+			Ok(line) => line,
+			Err(e) => {
+				let _ = e.print();
+
+				return;
+			},
+		};
+
+		if matches!(line.command, Command::Shell(_)) {
+			eprintln!("already in a shell");
+
+			return;
+		}
+		if let Err(e) = maybe_await!(dispatch(cx, &line)) {
+			error::report(e, line.error_format());
+		}
+	}
+}
+
+// Each line gets its own configuration and output format, the rest is shared with the shell.
+maybe_async! {
+	fn dispatch(cx: &AppContext, line: &Cli) -> Result<()> {
+		let config = line.load_config()?;
+		let out = Output::new(line.output_format(), cx.term.stdout_color, Theme::new(&config.theme)?);
+		let cx = AppContext {
+			dirs: cx.dirs.clone(),
+			config,
+			log_filter: cx.log_filter.clone(),
+			term: cx.term,
+			out,
+			shutdown: cx.shutdown.clone(),
+		};
+
+		// Not `Cli::run`, which already drives the shell. Boxed with the `async` feature, as the
+		// shell is itself run by `Cli::dispatch`.
+		#[cfg(feature = "async")]
+		return Box::pin(line.dispatch(&cx)).await;
+		#[cfg(not(feature = "async"))]
+		line.dispatch(&cx)
+	}
+}
+
+// Completes subcommands, aliases, flags and enumerated values from the command tree.
+struct ShellHelper(clap::Command);
+impl Completer for ShellHelper {
+	type Candidate = Pair;
+
+	fn complete(
+		&self,
+		line: &str,
+		pos: usize,
+		_: &Context<'_>,
+	) -> rustyline::Result<(usize, Vec<Pair>)> {
+		let line = &line[..pos];
+		let start = line.rfind(char::is_whitespace).map_or(0, |i| i + 1);
+		let word = &line[start..];
+		let mut cmd = &self.0;
+		let mut prev = None;
+
+		for w in line[..start].split_whitespace() {
+			if let Some(sub) = cmd.find_subcommand(w) {
+				cmd = sub;
+			}
+
+			prev = Some(w);
+		}
+
+		let values = prev
+			.and_then(|p| {
+				cmd.get_arguments().find(|a| {
+					p.strip_prefix("--").is_some_and(|l| a.get_long() == Some(l))
+						|| p.strip_prefix('-').is_some_and(|s| {
+							s.chars().count() == 1 && a.get_short() == s.chars().next()
+						})
+				})
+			})
+			.map(|a| a.get_possible_values())
+			.unwrap_or_default();
+		let mut candidates = if !values.is_empty() {
+			values
+				.iter()
+				.filter(|v| !v.is_hide_set())
+				.map(|v| v.get_name().to_owned())
+				.collect::<Vec<_>>()
+		} else if word.starts_with('-') {
+			cmd.get_arguments()
+				.filter(|a| !a.is_hide_set())
+				.flat_map(|a| {
+					a.get_long()
+						.map(|l| format!("--{l}"))
+						.into_iter()
+						.chain(a.get_short().map(|s| format!("-{s}")))
+				})
+				.collect()
+		} else {
+			cmd.get_subcommands()
+				.filter(|c| !c.is_hide_set())
+				.flat_map(|c| iter::once(c.get_name()).chain(c.get_all_aliases()))
+				.map(ToOwned::to_owned)
+				.collect()
+		};
+
+		candidates.retain(|c| c.starts_with(word));
+		candidates.sort();
+		candidates.dedup();
+
+		Ok((
+			start,
+			candidates
+				.into_iter()
+				.map(|c| Pair { replacement: format!("{c} "), display: c })
+				.collect(),
+		))
+	}
+}
+impl Helper for ShellHelper {}
+impl Hinter for ShellHelper {
+	type Hint = String;
+}
+impl Highlighter for ShellHelper {}
+impl Validator for ShellHelper {}
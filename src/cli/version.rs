@@ -0,0 +1,36 @@
+// crates.io
+use clap::Args;
+// self
+use crate::{
+	build_info::BUILD_INFO,
+	cli::{GlobalArgs, Run},
+	context::AppContext,
+	output::OutputFormat,
+	prelude::*,
+};
+
+/// Print version and build information.
+///
+/// Pass the global `--verbose` to include all build metadata, which machine-readable output
+/// formats always do.
+#[derive(Debug, Args)]
+pub struct VersionCmd {
+	/// Print all build metadata as JSON, shorthand for `--output json`.
+	#[arg(long)]
+	json: bool,
+}
+impl Run for VersionCmd {
+	maybe_async! {
+		fn run(&self, cx: &AppContext, global: &GlobalArgs) -> Result<()> {
+			let out = if self.json { cx.out.with_format(OutputFormat::Json) } else { cx.out };
+
+			if out.format() == OutputFormat::Text && global.verbose == 0 {
+				out.status(format_args!("{} {}", env!("CARGO_PKG_NAME"), BUILD_INFO.version));
+
+				return Ok(());
+			}
+
+			out.value(&BUILD_INFO)
+		}
+	}
+}
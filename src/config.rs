@@ -0,0 +1,467 @@
+// std
+use std::path::{Path, PathBuf};
+// crates.io
+use app_dirs2::AppDataType;
+use clap::CommandFactory;
+use figment::{
+	providers::{Env, Format, Json, Serialized, Toml, Yaml},
+	value::{Dict, Map},
+	Figment, Metadata, Profile, Provider,
+};
+use serde::{Deserialize, Serialize};
+use tracing_subscriber::EnvFilter;
+// self
+use crate::{
+	alias::{self, Aliases},
+	cli::Cli,
+	log::{LogFormat, LogRotation},
+	prelude::*,
+	runtime::RuntimeFlavor,
+	theme::{Theme, ThemePreset},
+	APP_INFO,
+};
+
+const FILE_STEM: &str = "config";
+const EXTENSIONS: [&str; 4] = ["toml", "json", "yaml", "yml"];
+
+/// Commented default config file, written by `config init`.
+pub const TEMPLATE: &str = r#"# Every key is optional, the values below are the built-in defaults.
+
+[alias]
+# User-defined subcommands expanding to other arguments, a string split on whitespace or a list.
+# They cannot shadow built-in subcommands, and may refer to other aliases. None by default, e.g.:
+# show = "config show"
+# verbose-show = ["--verbose", "show"]
+
+[log]
+# Log filter directives, same syntax as `RUST_LOG`.
+# filter = "info"
+
+[log.file]
+# Set to `false` to stop writing log files, same as `--no-log-file`.
+# enabled = true
+# Directory holding the log files, defaults to the user data directory.
+# dir = "/var/log/<NAME>"
+# Output format, one of `text`, `compact`, `pretty` or `json`.
+# format = "text"
+# When to start a new file, one of `minutely`, `hourly`, `daily`, `never` or `size`.
+# rotation = "daily"
+# Size in bytes that triggers a rotation, required by `size` rotation.
+# max_size = 10485760
+# Number of files to keep, unlimited by default.
+# max_files = 7
+# Days to keep files for, unlimited by default.
+# max_age_days = 30
+
+[log.console]
+# Only used by builds with the `dev` feature.
+# Output format, one of `text`, `compact`, `pretty` or `json`.
+# format = "text"
+
+[runtime]
+# Only used by builds with the `async` feature.
+# Scheduler, one of `current-thread` or `multi-thread`.
+# flavor = "multi-thread"
+# Worker threads of the `multi-thread` scheduler, one per CPU core by default.
+# worker_threads = 4
+
+[shutdown]
+# Seconds given to the running command to exit after `SIGINT` or `SIGTERM`.
+# grace_period_secs = 10
+
+[theme]
+# Styles of the help and of the command output, one of `default`, `monochrome` or
+# `high-contrast`.
+# preset = "default"
+# Override single styles of the preset with space separated words: `bold`, `dimmed`, `italic`,
+# `underline`, a color, `on` followed by a background color, or `none`. Colors are `black`, `red`,
+# `green`, `yellow`, `blue`, `magenta`, `cyan`, `white` and their `bright-` variants.
+# header = "bold underline bright-cyan"
+# usage = "bold"
+# literal = "bold"
+# placeholder = "italic"
+# error = "bold red"
+# valid = "green"
+# invalid = "yellow on black"
+
+[update]
+# Where `self-update` looks for releases: `github`, or a directory or HTTP(S) URL mirroring them
+# with a `latest` file holding the latest tag and a `<tag>/` directory per release.
+# source = "github"
+# Check for a newer release in the background, and print a notice after the command if there is
+# one. Never done in CI or when not attached to a terminal.
+# check = true
+# Hours between two checks.
+# check_interval_hours = 24
+"#;
+
+/// Application configuration.
+///
+/// Layers are merged in the following order, later ones taking precedence:
+/// 1. built-in defaults
+/// 2. the config file, TOML, JSON or YAML picked by its extension
+/// 3. environment variables prefixed with the upper-cased app name, `__` separating nested keys,
+///    plus `RUST_LOG`
+/// 4. command line flags
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+	/// User-defined subcommands.
+	pub alias: Aliases,
+	/// Logging.
+	pub log: LogConfig,
+	/// Async runtime, only used by builds with the `async` feature.
+	pub runtime: RuntimeConfig,
+	/// Signal handling.
+	pub shutdown: ShutdownConfig,
+	/// Styles.
+	pub theme: ThemeConfig,
+	/// Release lookup.
+	pub update: UpdateConfig,
+}
+impl Config {
+	/// Load and validate the configuration.
+	///
+	/// `path` overrides the config file lookup and must exist if given.
+	pub fn load(path: Option<&Path>, overrides: Overrides) -> Result<Self> {
+		let config = Self::figment(path, overrides)?.extract::<Self>()?;
+
+		config.validate().category(Category::Config)?;
+
+		Ok(config)
+	}
+
+	/// Build the layered figment without extracting it.
+	pub fn figment(path: Option<&Path>, overrides: Overrides) -> Result<Figment> {
+		let mut figment = Figment::from(Defaults);
+
+		if let Some(path) = resolve_path(path)? {
+			figment = match path.extension().and_then(|e| e.to_str()) {
+				Some("json") => figment.merge(Json::file_exact(path)),
+				Some("yaml" | "yml") => figment.merge(Yaml::file_exact(path)),
+				_ => figment.merge(Toml::file_exact(path)),
+			};
+		}
+
+		Ok(figment
+			.merge(Env::raw().only(&["RUST_LOG"]).map(|_| "log.filter".into()))
+			.merge(env())
+			.merge(overrides))
+	}
+
+	/// Check the values that serde alone cannot.
+	pub fn validate(&self) -> Result<()> {
+		if let Some(filter) = &self.log.filter {
+			EnvFilter::builder()
+				.parse(filter)
+				.wrap_err_with(|| format!("invalid `log.filter` value `{filter}`"))?;
+		}
+
+		let file = &self.log.file;
+
+		if file.rotation == LogRotation::Size && file.max_size.is_none_or(|s| s == 0) {
+			bail!("`log.file.rotation = \"size\"` requires a positive `log.file.max_size`");
+		}
+		if file.max_files == Some(0) {
+			bail!("`log.file.max_files` must be positive");
+		}
+		if self.runtime.worker_threads == Some(0) {
+			bail!("`runtime.worker_threads` must be positive");
+		}
+
+		Theme::new(&self.theme)?;
+		alias::check(&self.alias, &Cli::command())?;
+
+		if self.update.source.trim().is_empty() {
+			bail!("`update.source` must not be empty");
+		}
+
+		Ok(())
+	}
+}
+
+/// Logging configuration.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LogConfig {
+	/// Log filter directives, same syntax as `RUST_LOG`.
+	pub filter: Option<String>,
+	/// Log file output.
+	pub file: FileLogConfig,
+	/// Console output, only used by builds with the `dev` feature.
+	pub console: ConsoleLogConfig,
+}
+
+/// Log file configuration.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FileLogConfig {
+	/// Write log files at all.
+	pub enabled: bool,
+	/// Directory holding the log files, the user data directory if unset.
+	pub dir: Option<PathBuf>,
+	/// Output format.
+	pub format: LogFormat,
+	/// When to start a new file.
+	pub rotation: LogRotation,
+	/// Size in bytes that triggers a rotation, required by [`LogRotation::Size`].
+	pub max_size: Option<u64>,
+	/// Number of files to keep.
+	pub max_files: Option<usize>,
+	/// Days to keep files for.
+	pub max_age_days: Option<u64>,
+}
+impl Default for FileLogConfig {
+	fn default() -> Self {
+		Self {
+			enabled: true,
+			dir: None,
+			format: LogFormat::default(),
+			rotation: LogRotation::default(),
+			max_size: None,
+			max_files: None,
+			max_age_days: None,
+		}
+	}
+}
+
+/// Console log configuration.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConsoleLogConfig {
+	/// Output format.
+	pub format: LogFormat,
+}
+
+/// Async runtime configuration.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RuntimeConfig {
+	/// Scheduler.
+	pub flavor: RuntimeFlavor,
+	/// Worker threads of the multi-thread scheduler, one per CPU core if unset.
+	pub worker_threads: Option<usize>,
+}
+
+/// Shutdown configuration.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ShutdownConfig {
+	/// Seconds given to the running command to exit after a shutdown is requested.
+	pub grace_period_secs: u64,
+}
+impl Default for ShutdownConfig {
+	fn default() -> Self {
+		Self { grace_period_secs: 10 }
+	}
+}
+
+/// Theme configuration.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ThemeConfig {
+	/// Base styles.
+	pub preset: ThemePreset,
+	/// Overrides of single styles of the preset, see [`Theme`].
+	pub header: Option<String>,
+	pub usage: Option<String>,
+	pub literal: Option<String>,
+	pub placeholder: Option<String>,
+	pub error: Option<String>,
+	pub valid: Option<String>,
+	pub invalid: Option<String>,
+}
+
+/// Update configuration.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct UpdateConfig {
+	/// `github`, or a directory or HTTP(S) URL mirroring the releases.
+	pub source: String,
+	/// Notify about newer releases.
+	pub check: bool,
+	/// Hours between two update checks.
+	pub check_interval_hours: u64,
+}
+impl Default for UpdateConfig {
+	fn default() -> Self {
+		Self { source: "github".into(), check: true, check_interval_hours: 24 }
+	}
+}
+
+/// Built-in defaults, the bottom configuration layer.
+struct Defaults;
+impl Provider for Defaults {
+	fn metadata(&self) -> Metadata {
+		Metadata::named("default")
+	}
+
+	fn data(&self) -> figment::Result<Map<Profile, Dict>> {
+		Serialized::defaults(Config::default()).data()
+	}
+}
+
+/// Values set through command line flags, the top configuration layer.
+#[derive(Debug, Default)]
+pub struct Overrides(Figment);
+impl Overrides {
+	/// Set a dotted configuration key, e.g. `log.filter`.
+	pub fn set<T>(&mut self, key: &str, value: T)
+	where
+		T: Serialize,
+	{
+		self.0 = std::mem::take(&mut self.0).merge(Serialized::global(key, value));
+	}
+}
+impl Provider for Overrides {
+	fn metadata(&self) -> Metadata {
+		Metadata::named("command line")
+	}
+
+	fn data(&self) -> figment::Result<Map<Profile, Dict>> {
+		// Re-serialize to drop the inner tags, so values are attributed to this provider.
+		Serialized::globals(self.0.extract::<Dict>()?).data()
+	}
+}
+
+/// Directory holding the config file.
+pub fn dir() -> Result<PathBuf> {
+	Ok(app_dirs2::get_app_root(AppDataType::UserConfig, &APP_INFO)?)
+}
+
+/// Config file to read or create: `path` if given, otherwise the one in use or `config.toml` in
+/// [`dir`].
+pub fn path(path: Option<&Path>) -> Result<PathBuf> {
+	if let Some(path) = path {
+		return Ok(path.to_owned());
+	}
+
+	Ok(resolve_path(None)?.unwrap_or(dir()?.join(FILE_STEM).with_extension(EXTENSIONS[0])))
+}
+
+/// Config file in use: `path` if given, otherwise the first `config.{toml,json,yaml,yml}` found
+/// in [`dir`].
+pub fn resolve_path(path: Option<&Path>) -> Result<Option<PathBuf>> {
+	if let Some(path) = path {
+		if !path.is_file() {
+			return Err(eyre!("config file `{}` does not exist", path.display()))
+				.category(Category::Config);
+		}
+
+		return Ok(Some(path.to_owned()));
+	}
+
+	let dir = dir()?;
+
+	Ok(EXTENSIONS.iter().map(|e| dir.join(FILE_STEM).with_extension(e)).find(|p| p.is_file()))
+}
+
+/// Prefix of the environment variables read as configuration, e.g. `<NAME>_`.
+pub fn env_prefix() -> String {
+	format!("{}_", APP_INFO.name.to_uppercase().replace('-', "_"))
+}
+
+fn env() -> Env {
+	let prefix = env_prefix();
+	let sections = Defaults
+		.data()
+		.ok()
+		.and_then(|mut d| d.remove(&Profile::Default))
+		.map(|d| d.into_keys().collect::<Vec<_>>())
+		.unwrap_or_default();
+
+	// Only keep variables targeting a known section, so unrelated `<NAME>_*` variables are not
+	// mistaken for configuration.
+	Env::prefixed(&prefix).split("__").filter(move |k| {
+		let section = k.as_str().split('.').next().unwrap_or_default();
+
+		sections.iter().any(|s| s.eq_ignore_ascii_case(section))
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	// crates.io
+	use figment::Jail;
+	// self
+	use super::*;
+
+	fn overrides(entries: &[(&str, u64)]) -> Overrides {
+		let mut overrides = Overrides::default();
+
+		entries.iter().for_each(|(key, value)| overrides.set(key, value));
+
+		overrides
+	}
+
+	// `Jail` closures have to return the large `figment::Error`.
+	#[allow(clippy::result_large_err)]
+	#[test]
+	fn figment_should_work() {
+		Jail::expect_with(|jail| {
+			let var = |key: &str| format!("{}{key}", env_prefix());
+
+			jail.create_file("empty.toml", "")?;
+			jail.create_file("config.toml", "[shutdown]\ngrace_period_secs = 20")?;
+			jail.create_file("config.json", r#"{ "shutdown": { "grace_period_secs": 21 } }"#)?;
+			jail.create_file("config.yaml", "shutdown:\n  grace_period_secs: 22")?;
+			jail.create_file("config.yml", "shutdown:\n  grace_period_secs: 23")?;
+
+			// Layers from lowest to highest precedence: defaults, file, environment, flags.
+			for (file, env, flags, expected) in [
+				("empty.toml", None, &[][..], 10),
+				("config.toml", None, &[][..], 20),
+				("config.json", None, &[][..], 21),
+				("config.yaml", None, &[][..], 22),
+				("config.yml", None, &[][..], 23),
+				("config.toml", Some("30"), &[][..], 30),
+				("empty.toml", None, &[("shutdown.grace_period_secs", 40)][..], 40),
+				("config.toml", Some("30"), &[("shutdown.grace_period_secs", 40)][..], 40),
+			] {
+				// Restored once the jail is dropped.
+				jail.clear_env();
+
+				if let Some(value) = env {
+					jail.set_env(var("SHUTDOWN__GRACE_PERIOD_SECS"), value);
+				}
+
+				let config = Config::figment(Some(Path::new(file)), overrides(flags))
+					.unwrap()
+					.extract::<Config>();
+
+				assert_eq!(
+					config.unwrap().shutdown.grace_period_secs,
+					expected,
+					"{file} {env:?} {flags:?}"
+				);
+			}
+
+			jail.clear_env();
+
+			let filter = || {
+				Config::figment(Some(Path::new("empty.toml")), Overrides::default())
+					.unwrap()
+					.extract::<Config>()
+					.unwrap()
+					.log
+					.filter
+			};
+
+			assert_eq!(filter(), None);
+
+			jail.set_env("RUST_LOG", "debug");
+
+			assert_eq!(filter().as_deref(), Some("debug"));
+
+			// Unrelated variables sharing the prefix are ignored, not rejected as unknown fields.
+			jail.set_env(var("LOG__FILTER"), "warn");
+			jail.set_env(var("TOKEN"), "secret");
+			jail.set_env(var("CONFIG"), "elsewhere.toml");
+
+			assert_eq!(filter().as_deref(), Some("warn"));
+			assert_eq!(env().data()?[&Profile::Default].keys().collect::<Vec<_>>(), ["log"]);
+
+			Ok(())
+		});
+	}
+}
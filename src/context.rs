@@ -0,0 +1,102 @@
+// std
+use std::{
+	env,
+	io::{self, IsTerminal},
+	path::PathBuf,
+};
+// crates.io
+use app_dirs2::AppDataType;
+use clap::ColorChoice;
+// self
+use crate::{
+	config::{self, Config},
+	log::LogFilter,
+	output::Output,
+	prelude::*,
+	shutdown::Shutdown,
+	APP_INFO,
+};
+
+/// Process-wide state, built once in `main` and handed to every command.
+#[derive(Debug)]
+pub struct AppContext {
+	/// Per-user directories.
+	pub dirs: AppDirs,
+	/// Effective configuration.
+	pub config: Config,
+	/// Runtime control over the log filter.
+	pub log_filter: LogFilter,
+	/// What the attached terminal supports.
+	pub term: Terminal,
+	/// Printer for command results.
+	pub out: Output,
+	/// Cancellation token, set on `SIGINT` or `SIGTERM`.
+	pub shutdown: Shutdown,
+}
+
+/// Per-user directories derived from [`APP_INFO`], created on resolution.
+#[derive(Clone, Debug)]
+pub struct AppDirs {
+	/// Holds the config file and the log filter file.
+	pub config: PathBuf,
+	/// Holds the log files and the crash reports.
+	pub data: PathBuf,
+}
+impl AppDirs {
+	pub fn resolve() -> Result<Self> {
+		Ok(Self {
+			config: config::dir()?,
+			data: app_dirs2::get_app_root(AppDataType::UserData, &APP_INFO)?,
+		})
+	}
+}
+
+/// Terminal capabilities of the standard streams.
+#[derive(Clone, Copy, Debug)]
+pub struct Terminal {
+	/// Stdin is a terminal, so the user can be prompted.
+	pub stdin: bool,
+	/// Stdout is a terminal, as opposed to a pipe or a file.
+	pub stdout: bool,
+	/// Command output may be colored.
+	pub stdout_color: bool,
+	/// Logs and errors may be colored.
+	pub stderr_color: bool,
+}
+impl Terminal {
+	/// Inspect the standard streams, resolving `color` for each of them.
+	pub fn detect(color: ColorChoice) -> Self {
+		let stdout = io::stdout().is_terminal();
+
+		Self {
+			stdin: io::stdin().is_terminal(),
+			stdout,
+			stdout_color: use_color(color, stdout),
+			stderr_color: use_color(color, io::stderr().is_terminal()),
+		}
+	}
+
+	/// Both ends are attached to a terminal, e.g. to run an editor.
+	pub fn is_interactive(self) -> bool {
+		self.stdin && self.stdout
+	}
+}
+
+// `--color` wins, then `NO_COLOR` and `CLICOLOR_FORCE` as specified by `no-color.org` and
+// `bixense.com/clicolors`, then whether the stream is a terminal.
+fn use_color(choice: ColorChoice, is_terminal: bool) -> bool {
+	match choice {
+		ColorChoice::Always => true,
+		ColorChoice::Never => false,
+		ColorChoice::Auto => {
+			if env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty()) {
+				return false;
+			}
+			if env::var_os("CLICOLOR_FORCE").is_some_and(|v| !v.is_empty() && v != "0") {
+				return true;
+			}
+
+			is_terminal
+		},
+	}
+}
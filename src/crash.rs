@@ -0,0 +1,110 @@
+// std
+use std::{
+	backtrace::Backtrace,
+	collections::VecDeque,
+	env,
+	fmt::Write as _,
+	fs,
+	io::{self, Write},
+	panic::{self, PanicHookInfo},
+	path::{Path, PathBuf},
+	process,
+	sync::{Arc, Mutex},
+	thread,
+	time::{SystemTime, UNIX_EPOCH},
+};
+// crates.io
+use tracing_subscriber::fmt::MakeWriter;
+// self
+use crate::{build_info::BUILD_INFO, prelude::*, shutdown::Shutdown};
+
+const RECENT_LOGS: usize = 100;
+
+/// Ring buffer of the latest log lines, included in crash reports.
+#[derive(Clone, Debug, Default)]
+pub struct RecentLogs(Arc<Mutex<VecDeque<String>>>);
+impl Write for RecentLogs {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		let mut lines = self.0.lock().unwrap_or_else(|e| e.into_inner());
+
+		for line in String::from_utf8_lossy(buf).lines() {
+			if lines.len() == RECENT_LOGS {
+				lines.pop_front();
+			}
+
+			lines.push_back(line.to_owned());
+		}
+
+		Ok(buf.len())
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		Ok(())
+	}
+}
+impl<'a> MakeWriter<'a> for RecentLogs {
+	type Writer = Self;
+
+	fn make_writer(&'a self) -> Self::Writer {
+		self.clone()
+	}
+}
+
+/// Replace the panic hook with one writing a crash report into `dir`.
+///
+/// The previous hook still runs first, then the logs are flushed through `shutdown` and the
+/// process exits with [`Category::Internal`]'s code.
+pub fn install(recent_logs: RecentLogs, dir: PathBuf, shutdown: Shutdown) {
+	let default_hook = panic::take_hook();
+
+	panic::set_hook(Box::new(move |p| {
+		default_hook(p);
+
+		match write_report(p, &recent_logs, &dir) {
+			Ok(path) => eprintln!(
+				"\nA crash report has been written to `{}`.\n\
+				Please attach it to an issue at {}/issues.",
+				path.display(),
+				env!("CARGO_PKG_REPOSITORY"),
+			),
+			Err(e) => eprintln!("\nFailed to write the crash report: {e:#}"),
+		}
+
+		shutdown.flush();
+		process::exit(Category::Internal.exit_code().into());
+	}));
+}
+
+fn write_report(p: &PanicHookInfo, recent_logs: &RecentLogs, dir: &Path) -> Result<PathBuf> {
+	let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+	let path = dir.join(format!("crash-{}.txt", now.as_secs()));
+	let mut report = String::new();
+
+	writeln!(report, "# {} crash report", env!("CARGO_PKG_NAME"))?;
+	writeln!(report)?;
+	writeln!(report, "message:         {}", p.payload_as_str().unwrap_or("<non-string payload>"))?;
+	writeln!(
+		report,
+		"location:        {}",
+		p.location().map_or_else(|| "<unknown>".into(), ToString::to_string)
+	)?;
+	writeln!(report, "thread:          {}", thread::current().name().unwrap_or("<unnamed>"))?;
+	writeln!(report, "command:         {}", env::args().collect::<Vec<_>>().join(" "))?;
+	writeln!(report, "os:              {} {}", env::consts::OS, env::consts::ARCH)?;
+	writeln!(report, "unix time:       {}", now.as_secs())?;
+	writeln!(report, "{BUILD_INFO}")?;
+	writeln!(report)?;
+	writeln!(report, "## Backtrace")?;
+	writeln!(report, "{}", Backtrace::force_capture())?;
+	writeln!(report, "## Recent logs")?;
+
+	// Never block here, the panic may have happened while logging.
+	if let Ok(lines) = recent_logs.0.try_lock() {
+		lines.iter().try_for_each(|l| writeln!(report, "{l}"))?;
+	}
+
+	fs::create_dir_all(dir)?;
+	fs::write(&path, report)?;
+
+	Ok(path)
+}
@@ -0,0 +1,339 @@
+// std
+use std::{
+	error::Error as StdError,
+	fmt::{Debug, Display, Formatter, Result as FmtResult},
+	io::{self, ErrorKind},
+	mem,
+	process::ExitCode,
+};
+// crates.io
+use clap::ValueEnum;
+use color_eyre::{eyre::Report, Section};
+use serde::Serialize;
+// self
+use crate::prelude::*;
+
+/// How errors are printed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum ErrorFormat {
+	/// Colored report with causes, span trace and suggestions.
+	#[default]
+	Human,
+	/// Single JSON object on stderr, for scripts.
+	Json,
+}
+
+/// Error category, deciding the error code, the suggestion and the exit code.
+///
+/// Attach one with [`CategoryExt::category`]; errors without one are classified from their causes.
+/// Exit codes follow `sysexits.h`, and are part of the public interface: never renumber them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Category {
+	/// Invalid command line usage.
+	Usage,
+	/// Invalid or unreadable configuration.
+	Config,
+	/// Filesystem or other local I/O failure.
+	Io,
+	/// Network failure.
+	Network,
+	/// Stopped by a shutdown request, e.g. Ctrl-C.
+	Interrupted,
+	/// Bug in the program, such as a panic.
+	Internal,
+	/// Anything else.
+	Other,
+}
+impl Category {
+	/// Stable identifier, for scripts.
+	pub fn code(self) -> &'static str {
+		match self {
+			Self::Usage => "E-USAGE",
+			Self::Config => "E-CONFIG",
+			Self::Io => "E-IO",
+			Self::Network => "E-NETWORK",
+			Self::Interrupted => "E-INTERRUPTED",
+			Self::Internal => "E-INTERNAL",
+			Self::Other => "E-OTHER",
+		}
+	}
+
+	/// Process exit code.
+	pub fn exit_code(self) -> u8 {
+		match self {
+			// `EX_USAGE`.
+			Self::Usage => 64,
+			// `EX_CONFIG`.
+			Self::Config => 78,
+			// `EX_IOERR`.
+			Self::Io => 74,
+			// `EX_UNAVAILABLE`.
+			Self::Network => 69,
+			// 128 + `SIGINT`, like shells do.
+			Self::Interrupted => 130,
+			// `EX_SOFTWARE`.
+			Self::Internal => 70,
+			Self::Other => 1,
+		}
+	}
+
+	/// Hint shown along with the error.
+	pub fn suggestion(self) -> Option<String> {
+		match self {
+			Self::Usage => Some(format!("run `{} --help` for usage", env!("CARGO_PKG_NAME"))),
+			Self::Config => Some(format!(
+				"run `{} config show` to see the effective configuration and where each value \
+				comes from",
+				env!("CARGO_PKG_NAME")
+			)),
+			Self::Io => Some("check that the path exists and is accessible".into()),
+			Self::Network =>
+				Some("check the network connection and the proxy settings, then retry".into()),
+			Self::Internal => Some(format!(
+				"this is a bug, please report it at {}/issues",
+				env!("CARGO_PKG_REPOSITORY")
+			)),
+			Self::Interrupted | Self::Other => None,
+		}
+	}
+
+	/// Category of `report`: the outermost attached one, or one derived from the causes.
+	pub fn of(report: &Report) -> Self {
+		if let Some(category) = report.downcast_ref::<Self>() {
+			return *category;
+		}
+
+		report
+			.chain()
+			.find_map(|e| {
+				if e.is::<figment::Error>() {
+					return Some(Self::Config);
+				}
+
+				e.downcast_ref::<io::Error>().map(|e| match e.kind() {
+					ErrorKind::AddrNotAvailable
+					| ErrorKind::ConnectionAborted
+					| ErrorKind::ConnectionRefused
+					| ErrorKind::ConnectionReset
+					| ErrorKind::HostUnreachable
+					| ErrorKind::NetworkDown
+					| ErrorKind::NetworkUnreachable
+					| ErrorKind::NotConnected
+					| ErrorKind::TimedOut => Self::Network,
+					// `EINTR` is a failed system call, only a shutdown is an interruption.
+					_ => Self::Io,
+				})
+			})
+			.unwrap_or(Self::Other)
+	}
+}
+impl Display for Category {
+	fn fmt(&self, f: &mut Formatter) -> FmtResult {
+		f.write_str(match self {
+			Self::Usage => "invalid usage",
+			Self::Config => "invalid configuration",
+			Self::Io => "I/O failure",
+			Self::Network => "network failure",
+			Self::Interrupted => "interrupted",
+			Self::Internal => "internal error",
+			Self::Other => "unexpected failure",
+		})
+	}
+}
+impl From<Category> for ExitCode {
+	fn from(category: Category) -> Self {
+		Self::from(category.exit_code())
+	}
+}
+
+/// Exit with the given code without printing anything, e.g. when a plugin already reported its
+/// own error.
+#[derive(Debug)]
+pub struct Exit(pub u8);
+impl Display for Exit {
+	fn fmt(&self, f: &mut Formatter) -> FmtResult {
+		write!(f, "exited with code {}", self.0)
+	}
+}
+impl std::error::Error for Exit {}
+
+/// Attach a [`Category`] to an error, leaving its message, causes and sections as they are.
+pub trait CategoryExt<T> {
+	fn category(self, category: Category) -> Result<T>;
+}
+impl<T, E> CategoryExt<T> for Result<T, E>
+where
+	Self: WrapErr<T, E>,
+{
+	#[track_caller]
+	fn category(self, category: Category) -> Result<T> {
+		// A context keeps the report and its handler, the category is left out when printing.
+		self.wrap_err(category)
+	}
+}
+
+/// Print `report` to stderr in `format` and return the exit code to use.
+pub fn report(report: Report, format: ErrorFormat) -> ExitCode {
+	if let Some(Exit(code)) = report.downcast_ref::<Exit>() {
+		return ExitCode::from(*code);
+	}
+
+	let category = Category::of(&report);
+
+	match format {
+		ErrorFormat::Human => {
+			let report = match category.suggestion() {
+				Some(s) => report.suggestion(s),
+				None => report,
+			};
+
+			let report = format!("{:?}", Printed(&report, Message::chain(&report)));
+
+			match category {
+				// The location in the source is noise for errors the user has to fix.
+				Category::Usage | Category::Config =>
+					eprintln!("Error: {}", without_location(&report)),
+				_ => eprintln!("Error: {report}"),
+			}
+		},
+		ErrorFormat::Json => {
+			let json = serde_json::json!({
+				"error": {
+					"code": category.code(),
+					"category": category,
+					"message": messages(&report).next(),
+					"causes": messages(&report).skip(1).collect::<Vec<_>>(),
+					"suggestion": category.suggestion(),
+					"exit_code": category.exit_code(),
+				}
+			});
+
+			eprintln!("{json}");
+		},
+	}
+
+	category.into()
+}
+
+// The messages of `report`, without the attached category.
+fn messages(report: &Report) -> impl Iterator<Item = String> + '_ {
+	// The category is found through the report, so skip the context that holds it.
+	let category = report.downcast_ref::<Category>().map(|c| c as *const Category as usize);
+
+	report
+		.chain()
+		.filter(move |&e| {
+			let start = e as *const dyn StdError as *const u8 as usize;
+
+			category.is_none_or(|c| !(start..start + mem::size_of_val(e)).contains(&c))
+		})
+		.map(|e| e.to_string())
+}
+
+// Copy of the messages of a report, to print it through its handler without the category.
+#[derive(Debug)]
+struct Message {
+	text: String,
+	source: Option<Box<Message>>,
+}
+impl Message {
+	fn chain(report: &Report) -> Self {
+		let mut messages = messages(report).collect::<Vec<_>>().into_iter().rev();
+		let last = Self { text: messages.next().unwrap_or_default(), source: None };
+
+		messages.fold(last, |source, text| Self { text, source: Some(Box::new(source)) })
+	}
+}
+impl Display for Message {
+	fn fmt(&self, f: &mut Formatter) -> FmtResult {
+		f.write_str(&self.text)
+	}
+}
+impl StdError for Message {
+	fn source(&self) -> Option<&(dyn StdError + 'static)> {
+		self.source.as_deref().map(|e| e as _)
+	}
+}
+
+// Prints `.1` with the handler of `.0`, keeping its sections, span trace and location.
+struct Printed<'a>(&'a Report, Message);
+impl Debug for Printed<'_> {
+	fn fmt(&self, f: &mut Formatter) -> FmtResult {
+		self.0.handler().debug(&self.1, f)
+	}
+}
+
+// color-eyre only toggles the location section for all reports.
+fn without_location(report: &str) -> String {
+	let Some(start) = report.find("\n\nLocation:\n") else { return report.into() };
+	let end = report[start + 2..].find("\n\n").map_or(report.len(), |i| start + 2 + i);
+
+	format!("{}{}", &report[..start], &report[end..])
+}
+
+#[cfg(test)]
+mod tests {
+	// self
+	use super::*;
+
+	#[test]
+	fn category_should_keep_the_message() {
+		let report = Err::<(), _>(eyre!("no such alias"))
+			.wrap_err("failed to expand the aliases")
+			.category(Category::Usage)
+			.wrap_err("failed to parse the command line")
+			.unwrap_err();
+
+		assert_eq!(Category::of(&report), Category::Usage);
+		assert_eq!(
+			messages(&report).collect::<Vec<_>>(),
+			["failed to parse the command line", "failed to expand the aliases", "no such alias"]
+		);
+	}
+
+	#[test]
+	fn category_should_keep_the_report() {
+		let printed = |report: Result<()>| {
+			let report = report.unwrap_err();
+
+			format!("{:?}", Printed(&report, Message::chain(&report)))
+		};
+
+		// The handler, with its location and sections, is the one of the categorized error.
+		let line = line!() + 1;
+		let report = Err(eyre!("no such alias")).category(Category::Usage);
+
+		assert!(printed(report).contains(&format!("{}:{line}:", file!())));
+
+		let line = line!() + 1;
+		let report = Err(io::Error::from(ErrorKind::NotFound)).category(Category::Io);
+
+		assert!(printed(report).contains(&format!("{}:{line}:", file!())));
+	}
+
+	#[test]
+	fn category_should_be_derived_from_the_causes() {
+		let report = Err::<(), _>(io::Error::from(ErrorKind::TimedOut))
+			.wrap_err("failed to fetch")
+			.unwrap_err();
+
+		assert_eq!(Category::of(&report), Category::Network);
+		assert_eq!(Category::of(&io::Error::from(ErrorKind::Interrupted).into()), Category::Io);
+		assert_eq!(Category::of(&eyre!("failed")), Category::Other);
+		assert_eq!(
+			Category::of(&Err::<(), _>(report).category(Category::Io).unwrap_err()),
+			Category::Io
+		);
+	}
+
+	#[test]
+	fn without_location_should_only_cut_the_location() {
+		assert_eq!(
+			without_location("\n   0: oops\n\nLocation:\n   src/main.rs:1\n\nSuggestion: retry"),
+			"\n   0: oops\n\nSuggestion: retry"
+		);
+		assert_eq!(without_location("\n   0: oops\n\nLocation:\n   src/main.rs:1"), "\n   0: oops");
+		assert_eq!(without_location("\n   0: oops"), "\n   0: oops");
+	}
+}
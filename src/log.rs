@@ -0,0 +1,227 @@
+mod rolling;
+use rolling::{Retention, SizeRollingAppender, TimeRollingAppender};
+
+// std
+#[cfg(unix)] use std::path::PathBuf;
+use std::{fs, path::Path, time::Duration};
+// crates.io
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use tracing::Subscriber;
+use tracing_appender::{
+	non_blocking::{NonBlocking, WorkerGuard},
+	rolling::{RollingFileAppender, Rotation},
+};
+use tracing_subscriber::{
+	filter::LevelFilter,
+	fmt::{self, MakeWriter},
+	registry::LookupSpan,
+	reload::Handle,
+	EnvFilter, Layer, Registry,
+};
+// self
+use crate::{config::FileLogConfig, prelude::*, APP_INFO};
+
+const LEVELS: [LevelFilter; 6] = [
+	LevelFilter::OFF,
+	LevelFilter::ERROR,
+	LevelFilter::WARN,
+	LevelFilter::INFO,
+	LevelFilter::DEBUG,
+	LevelFilter::TRACE,
+];
+
+/// Log output format.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+	/// Human readable, one line per event.
+	#[default]
+	Text,
+	/// Like `text` but shorter.
+	Compact,
+	/// Multi-line, for development.
+	Pretty,
+	/// Newline-delimited JSON with span context, for log shipping.
+	Json,
+}
+
+/// When to start a new log file.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogRotation {
+	Minutely,
+	Hourly,
+	#[default]
+	Daily,
+	Never,
+	/// Once the file reaches `max_size` bytes.
+	Size,
+}
+
+/// Runtime control over the global log filter.
+#[derive(Clone, Debug)]
+pub struct LogFilter(Handle<EnvFilter, Registry>);
+impl LogFilter {
+	pub fn new(handle: Handle<EnvFilter, Registry>) -> Self {
+		Self(handle)
+	}
+
+	/// Replace the active filter with the given directives, e.g. `info,my_crate::net=debug`.
+	pub fn set(&self, directives: &str) -> Result<()> {
+		let filter = EnvFilter::builder()
+			.with_default_directive(LevelFilter::INFO.into())
+			.parse(directives)
+			.wrap_err_with(|| format!("invalid log filter `{directives}`"))?;
+
+		self.0.reload(filter)?;
+
+		tracing::info!("log filter set to `{directives}`");
+
+		Ok(())
+	}
+
+	/// Reload the filter from `path` every time the process receives `SIGHUP`.
+	///
+	/// The file holds the directives accepted by [`LogFilter::set`], so a long-running process
+	/// can be switched to `debug` for a single module with `kill -HUP`.
+	#[cfg(unix)]
+	pub fn reload_on_sighup(&self, path: PathBuf) -> Result<()> {
+		// crates.io
+		use signal_hook::{consts::SIGHUP, iterator::Signals};
+
+		let mut signals = Signals::new([SIGHUP])?;
+		let filter = self.clone();
+
+		std::thread::spawn(move || {
+			for _ in signals.forever() {
+				match std::fs::read_to_string(&path) {
+					Ok(directives) =>
+						if let Err(e) = filter.set(directives.trim()) {
+							tracing::warn!("{e:#}");
+						},
+					Err(e) => tracing::warn!("failed to read `{}`: {e}", path.display()),
+				}
+			}
+		});
+
+		Ok(())
+	}
+}
+
+/// Name of the file read by [`LogFilter::reload_on_sighup`], in the config directory.
+#[cfg(unix)]
+pub const FILTER_FILE: &str = "log-filter";
+
+/// Open the non-blocking log file writer, `None` if file logging is disabled.
+///
+/// `default_dir` is used unless the config sets a directory. Files past the retention policy are
+/// pruned before logging starts, then on every rotation.
+pub fn file_writer(
+	config: &FileLogConfig,
+	default_dir: &Path,
+) -> Result<Option<(NonBlocking, WorkerGuard)>> {
+	if !config.enabled {
+		return Ok(None);
+	}
+
+	let dir = config.dir.as_deref().unwrap_or(default_dir);
+	let prefix = APP_INFO.name;
+	let retention = Retention {
+		max_files: config.max_files,
+		max_age: config.max_age_days.map(|d| Duration::from_secs(d * 24 * 60 * 60)),
+	};
+	let (rotation, period) = match config.rotation {
+		LogRotation::Minutely => (Rotation::MINUTELY, Some(60)),
+		LogRotation::Hourly => (Rotation::HOURLY, Some(60 * 60)),
+		LogRotation::Daily => (Rotation::DAILY, Some(24 * 60 * 60)),
+		LogRotation::Never => (Rotation::NEVER, None),
+		LogRotation::Size => {
+			// Validated along with the config.
+			let max_size = config.max_size.unwrap_or(u64::MAX);
+			let appender = SizeRollingAppender::new(dir, prefix, max_size, retention.clone())?;
+
+			retention.prune(dir, prefix, Some(&dir.join(format!("{prefix}.log"))))?;
+
+			return Ok(Some(tracing_appender::non_blocking(appender)));
+		},
+	};
+	let mut builder = RollingFileAppender::builder()
+		.rotation(rotation)
+		.filename_prefix(prefix)
+		.filename_suffix("log");
+
+	if let Some(n) = config.max_files {
+		builder = builder.max_log_files(n);
+	}
+
+	// Before opening the active file, which is unknown to the pruning and must not be deleted.
+	fs::create_dir_all(dir)?;
+	retention.prune(dir, prefix, None)?;
+
+	let appender = builder.build(dir)?;
+
+	let Some(period) = period else {
+		return Ok(Some(tracing_appender::non_blocking(appender)));
+	};
+
+	let appender =
+		TimeRollingAppender::new(appender, dir, prefix, Duration::from_secs(period), retention);
+
+	Ok(Some(tracing_appender::non_blocking(appender)))
+}
+
+/// Default level shifted by `verbose - quiet` steps from `INFO`.
+pub fn verbosity(verbose: u8, quiet: u8) -> LevelFilter {
+	let i = (3 + verbose as isize - quiet as isize).clamp(0, LEVELS.len() as isize - 1);
+
+	LEVELS[i as usize]
+}
+
+/// Build the log file layer.
+pub fn file_layer<S, W>(format: LogFormat, writer: W) -> Box<dyn Layer<S> + Send + Sync>
+where
+	S: Subscriber + for<'a> LookupSpan<'a>,
+	W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+	format_layer(fmt::layer().with_ansi(false).with_writer(writer), format)
+}
+
+/// Build the development console layer, printing to stderr with span timings.
+#[cfg(feature = "dev")]
+pub fn console_layer<S>(format: LogFormat, color: bool) -> Box<dyn Layer<S> + Send + Sync>
+where
+	S: Subscriber + for<'a> LookupSpan<'a>,
+{
+	format_layer(
+		fmt::layer()
+			.with_ansi(color)
+			.with_span_events(fmt::format::FmtSpan::CLOSE)
+			.with_writer(std::io::stderr),
+		format,
+	)
+}
+
+// Timestamps are RFC 3339 in UTC for every format.
+fn format_layer<S, W>(
+	layer: fmt::Layer<S, fmt::format::DefaultFields, fmt::format::Format, W>,
+	format: LogFormat,
+) -> Box<dyn Layer<S> + Send + Sync>
+where
+	S: Subscriber + for<'a> LookupSpan<'a>,
+	W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+	match format {
+		LogFormat::Text => layer.boxed(),
+		LogFormat::Compact => layer.compact().boxed(),
+		LogFormat::Pretty => layer.pretty().boxed(),
+		LogFormat::Json => layer
+			.json()
+			.with_current_span(true)
+			.with_span_list(true)
+			.with_thread_ids(true)
+			.with_file(true)
+			.with_line_number(true)
+			.boxed(),
+	}
+}
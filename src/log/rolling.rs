@@ -0,0 +1,357 @@
+// std
+use std::{
+	fs::{self, File, OpenOptions},
+	io::{self, Write},
+	path::{Path, PathBuf},
+	time::{Duration, SystemTime, UNIX_EPOCH},
+};
+// crates.io
+use tracing_appender::rolling::RollingFileAppender;
+
+/// Which log files to keep.
+#[derive(Clone, Debug)]
+pub struct Retention {
+	/// Keep at most this many files, newest first.
+	pub max_files: Option<usize>,
+	/// Delete files last modified longer ago than this.
+	pub max_age: Option<Duration>,
+}
+impl Retention {
+	/// Delete the `<prefix>.*.log` files in `dir` that fall outside the policy.
+	///
+	/// `active` is never deleted.
+	pub fn prune(&self, dir: &Path, prefix: &str, active: Option<&Path>) -> io::Result<()> {
+		if self.max_files.is_none() && self.max_age.is_none() {
+			return Ok(());
+		}
+
+		let mut files = fs::read_dir(dir)?
+			.filter_map(|e| {
+				let e = e.ok()?;
+				let name = e.file_name().into_string().ok()?;
+
+				if !name.starts_with(&format!("{prefix}.")) || !name.ends_with(".log") {
+					return None;
+				}
+
+				Some((e.path(), e.metadata().ok()?.modified().ok()?))
+			})
+			.filter(|(p, _)| Some(p.as_path()) != active)
+			.collect::<Vec<_>>();
+
+		files.sort_by(|(_, a), (_, b)| b.cmp(a));
+
+		// The active file takes one of the slots.
+		let keep = self.max_files.map(|n| n.saturating_sub(active.is_some() as usize));
+		let now = SystemTime::now();
+
+		for (i, (path, modified)) in files.into_iter().enumerate() {
+			let too_many = keep.is_some_and(|n| i >= n);
+			let too_old = self
+				.max_age
+				.is_some_and(|age| now.duration_since(modified).unwrap_or_default() > age);
+
+			if too_many || too_old {
+				fs::remove_file(path)?;
+			}
+		}
+
+		Ok(())
+	}
+}
+
+/// Appender starting a new file once the current one would grow past `max_size` bytes.
+///
+/// Writes into `<prefix>.log` and moves full files to `<prefix>.<unix millis>.log`.
+#[derive(Debug)]
+pub struct SizeRollingAppender {
+	dir: PathBuf,
+	prefix: String,
+	max_size: u64,
+	retention: Retention,
+	file: File,
+	size: u64,
+}
+impl SizeRollingAppender {
+	pub fn new(dir: &Path, prefix: &str, max_size: u64, retention: Retention) -> io::Result<Self> {
+		fs::create_dir_all(dir)?;
+
+		let path = dir.join(format!("{prefix}.log"));
+		let file = OpenOptions::new().create(true).append(true).open(&path)?;
+		let size = file.metadata()?.len();
+
+		Ok(Self { dir: dir.to_owned(), prefix: prefix.to_owned(), max_size, retention, file, size })
+	}
+
+	fn rotate(&mut self) -> io::Result<()> {
+		let active = self.dir.join(format!("{}.log", self.prefix));
+		let mut millis =
+			SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis();
+		let mut rotated = self.dir.join(format!("{}.{millis}.log", self.prefix));
+
+		// Several rotations may happen within the same millisecond.
+		while rotated.exists() {
+			millis += 1;
+			rotated = self.dir.join(format!("{}.{millis}.log", self.prefix));
+		}
+
+		self.file.flush()?;
+		fs::rename(&active, rotated)?;
+
+		self.file = OpenOptions::new().create(true).append(true).open(&active)?;
+		self.size = 0;
+
+		self.retention.prune(&self.dir, &self.prefix, Some(&active))
+	}
+}
+impl Write for SizeRollingAppender {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		if self.size != 0 && self.size + buf.len() as u64 > self.max_size {
+			self.rotate()?;
+		}
+
+		let n = self.file.write(buf)?;
+
+		self.size += n as u64;
+
+		Ok(n)
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		self.file.flush()
+	}
+}
+
+/// [`RollingFileAppender`] applying the [`Retention`] on every rotation, as `tracing-appender` only
+/// caps the number of files.
+///
+/// Its rotations happen on UTC boundaries of `period`, which are tracked to notice them.
+#[derive(Debug)]
+pub struct TimeRollingAppender {
+	appender: RollingFileAppender,
+	dir: PathBuf,
+	prefix: String,
+	period: Duration,
+	retention: Retention,
+	current: u64,
+}
+impl TimeRollingAppender {
+	pub fn new(
+		appender: RollingFileAppender,
+		dir: &Path,
+		prefix: &str,
+		period: Duration,
+		retention: Retention,
+	) -> Self {
+		let current = Self::index(period);
+
+		Self {
+			appender,
+			dir: dir.to_owned(),
+			prefix: prefix.to_owned(),
+			period,
+			retention,
+			current,
+		}
+	}
+
+	fn index(period: Duration) -> u64 {
+		SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs()
+			/ period.as_secs().max(1)
+	}
+}
+impl Write for TimeRollingAppender {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		let n = self.appender.write(buf)?;
+		let index = Self::index(self.period);
+
+		// After the write, so the new file exists and is the newest one.
+		if index != self.current {
+			self.current = index;
+			self.retention.prune(&self.dir, &self.prefix, None)?;
+		}
+
+		Ok(n)
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		self.appender.flush()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	// std
+	use std::process;
+	// self
+	use super::*;
+
+	const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+	fn temp_dir(name: &str) -> PathBuf {
+		let dir = std::env::temp_dir().join(format!("rolling-{name}-{}", process::id()));
+
+		let _ = fs::remove_dir_all(&dir);
+
+		fs::create_dir_all(&dir).unwrap();
+
+		dir
+	}
+
+	// Create `name` in `dir`, last modified `age` ago.
+	fn touch(dir: &Path, name: &str, age: Duration) -> PathBuf {
+		let path = dir.join(name);
+
+		File::create(&path).unwrap().set_modified(SystemTime::now() - age).unwrap();
+
+		path
+	}
+
+	fn names(dir: &Path) -> Vec<String> {
+		let mut names = fs::read_dir(dir)
+			.unwrap()
+			.map(|e| e.unwrap().file_name().into_string().unwrap())
+			.collect::<Vec<_>>();
+
+		names.sort();
+
+		names
+	}
+
+	#[test]
+	fn prune_should_keep_the_newest_files() {
+		let dir = temp_dir("max-files");
+
+		for (i, name) in ["app.1.log", "app.2.log", "app.3.log", "app.4.log"].iter().enumerate() {
+			touch(&dir, name, Duration::from_secs(60 * (4 - i as u64)));
+		}
+
+		touch(&dir, "other.1.log", DAY);
+		touch(&dir, "app.1.txt", DAY);
+
+		Retention { max_files: Some(2), max_age: None }.prune(&dir, "app", None).unwrap();
+
+		assert_eq!(names(&dir), ["app.1.txt", "app.3.log", "app.4.log", "other.1.log"]);
+
+		fs::remove_dir_all(dir).unwrap();
+	}
+
+	#[test]
+	fn prune_should_count_but_keep_the_active_file() {
+		let dir = temp_dir("active");
+		let active = touch(&dir, "app.log", 3 * DAY);
+
+		touch(&dir, "app.1.log", 2 * DAY);
+		touch(&dir, "app.2.log", Duration::ZERO);
+
+		Retention { max_files: Some(2), max_age: Some(DAY) }
+			.prune(&dir, "app", Some(&active))
+			.unwrap();
+
+		assert_eq!(names(&dir), ["app.2.log", "app.log"]);
+
+		Retention { max_files: Some(1), max_age: None }.prune(&dir, "app", Some(&active)).unwrap();
+
+		assert_eq!(names(&dir), ["app.log"]);
+
+		fs::remove_dir_all(dir).unwrap();
+	}
+
+	#[test]
+	fn prune_should_delete_old_files() {
+		let dir = temp_dir("max-age");
+
+		touch(&dir, "app.1.log", 3 * DAY);
+		touch(&dir, "app.2.log", Duration::ZERO);
+
+		Retention { max_files: None, max_age: Some(DAY) }.prune(&dir, "app", None).unwrap();
+
+		assert_eq!(names(&dir), ["app.2.log"]);
+
+		fs::remove_dir_all(dir).unwrap();
+	}
+
+	#[test]
+	fn size_rolling_appender_should_rotate() {
+		let dir = temp_dir("size");
+		let mut appender = SizeRollingAppender::new(
+			&dir,
+			"app",
+			10,
+			Retention { max_files: Some(3), max_age: None },
+		)
+		.unwrap();
+
+		// A line larger than the limit still goes into a single file.
+		for line in ["0123456789abc\n", "0123\n", "4567\n", "89ab\n", "cdef\n", "ghij\n"] {
+			appender.write_all(line.as_bytes()).unwrap();
+		}
+
+		appender.flush().unwrap();
+
+		let files = names(&dir);
+		let rotated = files.iter().filter(|n| *n != "app.log").collect::<Vec<_>>();
+
+		// The oldest rotated files are pruned, the active one takes a slot.
+		assert_eq!(files.len(), 3);
+		assert_eq!(fs::read_to_string(dir.join("app.log")).unwrap(), "ghij\n");
+		assert_eq!(fs::read_to_string(dir.join(rotated[1])).unwrap(), "89ab\ncdef\n");
+		assert_eq!(fs::read_to_string(dir.join(rotated[0])).unwrap(), "0123\n4567\n");
+
+		fs::remove_dir_all(dir).unwrap();
+	}
+
+	#[test]
+	fn time_rolling_appender_should_prune_on_rotation() {
+		let dir = temp_dir("time");
+
+		touch(&dir, "app.1.log", 3 * DAY);
+		touch(&dir, "app.2.log", Duration::ZERO);
+
+		let appender = RollingFileAppender::builder()
+			.rotation(tracing_appender::rolling::Rotation::DAILY)
+			.filename_prefix("app")
+			.filename_suffix("log")
+			.build(&dir)
+			.unwrap();
+		let mut appender = TimeRollingAppender::new(
+			appender,
+			&dir,
+			"app",
+			DAY,
+			Retention { max_files: None, max_age: Some(DAY) },
+		);
+
+		appender.write_all(b"0123\n").unwrap();
+
+		assert_eq!(names(&dir).len(), 3);
+
+		// As if the day changed since the last write.
+		appender.current -= 1;
+		appender.write_all(b"4567\n").unwrap();
+
+		assert_eq!(names(&dir).len(), 2);
+		assert!(!names(&dir).contains(&"app.1.log".into()));
+
+		fs::remove_dir_all(dir).unwrap();
+	}
+
+	#[test]
+	fn size_rolling_appender_should_append_to_the_active_file() {
+		let dir = temp_dir("append");
+
+		fs::write(dir.join("app.log"), "01234567").unwrap();
+
+		let mut appender =
+			SizeRollingAppender::new(&dir, "app", 10, Retention { max_files: None, max_age: None })
+				.unwrap();
+
+		appender.write_all(b"89ab").unwrap();
+		appender.flush().unwrap();
+
+		assert_eq!(names(&dir).len(), 2);
+		assert_eq!(fs::read_to_string(dir.join("app.log")).unwrap(), "89ab");
+
+		fs::remove_dir_all(dir).unwrap();
+	}
+}
@@ -2,20 +2,55 @@
 
 #![deny(clippy::all, missing_docs, unused_crate_dependencies)]
 
+mod alias;
+
+mod build_info;
+
 mod cli;
 use cli::Cli;
 
+mod config;
+
+mod context;
+use context::{AppContext, AppDirs, Terminal};
+
+mod crash;
+use crash::RecentLogs;
+
+mod log;
+use log::LogFilter;
+
+mod error;
+
+mod output;
+use output::Output;
+
+mod plugin;
+
+mod runtime;
+
+mod shutdown;
+use shutdown::Shutdown;
+
+mod theme;
+use theme::Theme;
+
+mod update;
+use update::Notifier;
+
 mod prelude {
-	pub use anyhow::Result;
+	pub use color_eyre::eyre::{bail, eyre, ContextCompat, Result, WrapErr};
+
+	pub use crate::error::{Category, CategoryExt};
 }
 use prelude::*;
 
 // std
-use std::{panic, process};
+use std::{env, process::ExitCode, time::Duration};
 // crates.io
-use app_dirs2::{AppDataType, AppInfo};
-use clap::Parser;
-use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use app_dirs2::AppInfo;
+use color_eyre::config::{HookBuilder, Theme as EyreTheme};
+use tracing_error::ErrorLayer;
 use tracing_subscriber::{
 	filter::LevelFilter, fmt, layer::SubscriberExt, reload::Layer, util::SubscriberInitExt,
 	EnvFilter,
@@ -23,35 +58,97 @@ use tracing_subscriber::{
 
 const APP_INFO: AppInfo = AppInfo { name: "<NAME>", author: "hack.ink" };
 
-fn main() -> Result<()> {
-	color_eyre::install().unwrap();
-
-	let (non_blocking, _guard) = tracing_appender::non_blocking(
-		RollingFileAppender::builder()
-			.rotation(Rotation::DAILY)
-			.filename_suffix("log")
-			.build(app_dirs2::get_app_root(AppDataType::UserData, &APP_INFO).unwrap())?,
-	);
-	let filter =
-		EnvFilter::builder().with_default_directive(LevelFilter::INFO.into()).from_env_lossy();
+fn main() -> ExitCode {
+	// Development builds always show full backtraces with source snippets.
+	if cfg!(feature = "dev") && env::var_os("RUST_BACKTRACE").is_none() {
+		env::set_var("RUST_BACKTRACE", "full");
+	}
+
+	let args = env::args_os().collect::<Vec<_>>();
+	let color = cli::color_arg(&args);
+
+	// Before anything creating an error report, e.g. loading the help theme, which would install
+	// the default hook.
+	HookBuilder::default()
+		.theme(if Terminal::detect(color).stderr_color {
+			EyreTheme::dark()
+		} else {
+			EyreTheme::new()
+		})
+		.install()
+		.unwrap();
+
+	let cli = match Cli::try_parse_styled(args, color) {
+		Ok(cli) => cli,
+		Err(e) => {
+			let _ = e.print();
+
+			// `--help` and `--version` are not errors.
+			return if e.use_stderr() { Category::Usage.into() } else { ExitCode::SUCCESS };
+		},
+	};
+	let term = Terminal::detect(cli.color());
+	let error_format = cli.error_format();
+	let shutdown = Shutdown::default();
+	let code = match run(cli, term, &shutdown) {
+		Ok(()) => ExitCode::SUCCESS,
+		Err(e) => error::report(e, error_format),
+	};
+
+	shutdown.flush();
+
+	// Interrupted runs exit with `128 + signal`, even if the command wound down cleanly.
+	shutdown.exit_code().unwrap_or(code)
+}
+
+fn run(cli: Cli, term: Terminal, shutdown: &Shutdown) -> Result<()> {
+	let dirs = AppDirs::resolve()?;
+	let config = cli.load_config()?;
+	let (file_writer, guard) = log::file_writer(&config.log.file, &dirs.data)?.unzip();
+
+	shutdown.hold(guard);
+
+	// `RUST_LOG` is already merged into the config.
+	let filter = EnvFilter::builder()
+		.with_default_directive(LevelFilter::INFO.into())
+		.parse_lossy(config.log.filter.as_deref().unwrap_or_default());
 	let (reloadable_filter, filter_handle) = Layer::new(filter);
-	let file_layer = fmt::layer().with_ansi(false).with_writer(non_blocking);
-	let subscriber = tracing_subscriber::registry().with(reloadable_filter).with(file_layer);
+	let file_layer = file_writer.map(|w| log::file_layer(config.log.file.format, w));
+	let recent_logs = RecentLogs::default();
+	let recent_logs_layer = fmt::layer().with_ansi(false).with_writer(recent_logs.clone());
+	let subscriber = tracing_subscriber::registry()
+		.with(reloadable_filter)
+		.with(file_layer)
+		.with(recent_logs_layer)
+		.with(ErrorLayer::default());
 	#[cfg(feature = "dev")]
-	let console_layer = fmt::layer();
+	let console_layer = log::console_layer(config.log.console.format, term.stderr_color);
 	#[cfg(feature = "dev")]
 	let subscriber = subscriber.with(console_layer);
 
 	subscriber.init();
 
-	let default_hook = panic::take_hook();
+	crash::install(recent_logs, dirs.data.join("crashes"), shutdown.clone());
+	shutdown.install(Duration::from_secs(config.shutdown.grace_period_secs))?;
+
+	let log_filter = LogFilter::new(filter_handle);
+
+	#[cfg(unix)]
+	log_filter.reload_on_sighup(dirs.config.join(log::FILTER_FILE))?;
+
+	let out = Output::new(cli.output_format(), term.stdout_color, Theme::new(&config.theme)?);
+	let cx = AppContext { dirs, config, log_filter, term, out, shutdown: shutdown.clone() };
+
+	let notifier = cli.notifies_updates().then(|| Notifier::spawn(&cx));
 
-	panic::set_hook(Box::new(move |p| {
-		default_hook(p);
+	#[cfg(feature = "async")]
+	runtime::build(&cx.config.runtime)?.block_on(cli.run(&cx))?;
+	#[cfg(not(feature = "async"))]
+	cli.run(&cx)?;
 
-		process::abort();
-	}));
-	Cli::parse().run(filter_handle)?;
+	if let Some(notifier) = notifier {
+		notifier.finish();
+	}
 
 	Ok(())
 }
@@ -0,0 +1,197 @@
+// std
+use std::{
+	fmt::Display,
+	io::{self, ErrorKind, Write},
+};
+// crates.io
+use clap::{builder::styling::Style, ValueEnum};
+use serde::Serialize;
+// self
+use crate::{error::Exit, prelude::*, theme::Theme};
+
+/// How command results are printed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+	/// Human readable text, lists as aligned tables.
+	#[default]
+	#[value(alias = "table")]
+	Text,
+	/// Pretty-printed JSON, lists as arrays.
+	Json,
+	/// One compact JSON document per line, one line per list item.
+	Ndjson,
+	/// YAML, lists as sequences.
+	Yaml,
+}
+
+/// A list item, rendered as a table row in [`OutputFormat::Text`].
+pub trait Tabular: Serialize {
+	/// Column titles.
+	const HEADER: &'static [&'static str];
+
+	/// Cells, one per column.
+	fn row(&self) -> Vec<String>;
+}
+
+/// Printer commands write their typed results into.
+///
+/// Results go to stdout in the selected format; status messages go to stdout as text, and to
+/// stderr otherwise so that machine-readable output stays parseable.
+#[derive(Clone, Copy, Debug)]
+pub struct Output {
+	format: OutputFormat,
+	color: bool,
+	theme: Theme,
+}
+impl Output {
+	/// `color` and `theme` only apply to [`OutputFormat::Text`].
+	pub fn new(format: OutputFormat, color: bool, theme: Theme) -> Self {
+		Self { format, color, theme }
+	}
+
+	pub fn format(self) -> OutputFormat {
+		self.format
+	}
+
+	/// Same printer with another format.
+	pub fn with_format(self, format: OutputFormat) -> Self {
+		Self { format, ..self }
+	}
+
+	/// Print a single result, through its `Display` implementation for text.
+	pub fn value<T>(&self, value: &T) -> Result<()>
+	where
+		T: Display + Serialize,
+	{
+		let text = match self.format {
+			OutputFormat::Text => format!("{value}\n"),
+			OutputFormat::Json => format!("{}\n", serde_json::to_string_pretty(value)?),
+			OutputFormat::Ndjson => format!("{}\n", serde_json::to_string(value)?),
+			OutputFormat::Yaml => serde_yaml::to_string(value)?,
+		};
+
+		stdout(text.as_bytes())
+	}
+
+	/// Print a list of results.
+	pub fn list<T>(&self, items: &[T]) -> Result<()>
+	where
+		T: Tabular,
+	{
+		let text = match self.format {
+			OutputFormat::Text => {
+				let header = if self.color { self.theme.header } else { Style::new() };
+
+				table(T::HEADER, items.iter().map(T::row), header)
+			},
+			OutputFormat::Json => format!("{}\n", serde_json::to_string_pretty(items)?),
+			OutputFormat::Ndjson => items
+				.iter()
+				.map(|i| Ok(format!("{}\n", serde_json::to_string(i)?)))
+				.collect::<Result<_>>()?,
+			OutputFormat::Yaml => serde_yaml::to_string(items)?,
+		};
+
+		stdout(text.as_bytes())
+	}
+
+	/// Print a message about what the command did, e.g. ``wrote `config.toml` ``.
+	pub fn status<T>(&self, message: T)
+	where
+		T: Display,
+	{
+		match self.format {
+			// A closed stdout is reported by the next result.
+			OutputFormat::Text => {
+				let _ = stdout(format!("{message}\n").as_bytes());
+			},
+			_ => eprintln!("{message}"),
+		}
+	}
+}
+
+/// Write `bytes` to stdout.
+///
+/// A closed stdout, e.g. piped into `head`, ends the command quietly with [`Exit`]`(0)`.
+pub fn stdout(bytes: &[u8]) -> Result<()> {
+	write_all(io::stdout().lock(), bytes)
+}
+
+fn write_all<W>(mut w: W, bytes: &[u8]) -> Result<()>
+where
+	W: Write,
+{
+	match w.write_all(bytes).and_then(|()| w.flush()) {
+		Err(e) if e.kind() == ErrorKind::BrokenPipe => Err(Exit(0).into()),
+		r => Ok(r?),
+	}
+}
+
+// Columns are separated by two spaces, the last one is not padded.
+fn table<I>(header: &[&str], rows: I, header_style: Style) -> String
+where
+	I: Iterator<Item = Vec<String>>,
+{
+	let rows = [header.iter().map(|h| h.to_uppercase()).collect()]
+		.into_iter()
+		.chain(rows)
+		.collect::<Vec<Vec<_>>>();
+	let mut widths = vec![0; header.len()];
+
+	for row in &rows {
+		for (w, cell) in widths.iter_mut().zip(row) {
+			*w = (*w).max(cell.chars().count());
+		}
+	}
+
+	let mut table = String::new();
+
+	for (r, row) in rows.into_iter().enumerate() {
+		let last = row.len().saturating_sub(1);
+		let style = if r == 0 { header_style } else { Style::new() };
+
+		for (i, (cell, w)) in row.iter().zip(&widths).enumerate() {
+			// Pad outside of the escape codes so that they do not count towards the width.
+			let pad = if i == last { 0 } else { w - cell.chars().count() + 2 };
+
+			table.push_str(&format!("{style}{cell}{style:#}{:pad$}", ""));
+		}
+
+		table.push('\n');
+	}
+
+	table
+}
+
+#[cfg(test)]
+mod tests {
+	// std
+	use std::process::ExitCode;
+	// self
+	use super::*;
+
+	struct Closed;
+	impl Write for Closed {
+		fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+			Err(ErrorKind::BrokenPipe.into())
+		}
+
+		fn flush(&mut self) -> io::Result<()> {
+			Ok(())
+		}
+	}
+
+	#[test]
+	fn write_all_should_exit_quietly_on_a_closed_stdout() {
+		let e = write_all(Closed, b"x").unwrap_err();
+
+		assert!(matches!(e.downcast_ref::<Exit>(), Some(Exit(0))));
+		assert_eq!(crate::error::report(e, crate::error::ErrorFormat::Json), ExitCode::SUCCESS);
+
+		let mut buf = Vec::new();
+
+		write_all(&mut buf, b"x").unwrap();
+
+		assert_eq!(buf, b"x");
+	}
+}
@@ -0,0 +1,137 @@
+// std
+use std::{
+	env,
+	ffi::OsString,
+	fs, iter,
+	path::{Path, PathBuf},
+	process::{Command, Stdio},
+};
+// crates.io
+use clap::ValueEnum;
+// self
+use crate::{cli::GlobalArgs, config, context::AppContext, error::Exit, prelude::*};
+
+const PREFIX: &str = concat!(env!("CARGO_PKG_NAME"), "-");
+
+/// Executable named `<NAME>-<SUBCOMMAND>`, run for the unknown subcommand `<SUBCOMMAND>`.
+#[derive(Debug)]
+pub struct Plugin {
+	/// Subcommand it provides.
+	pub name: String,
+	pub path: PathBuf,
+}
+impl Plugin {
+	/// The plugin providing `name`, looked up like [`discover`] does.
+	pub fn find(name: &str, data_dir: &Path) -> Option<Self> {
+		search_path(data_dir)
+			.into_iter()
+			.map(|d| d.join(format!("{PREFIX}{name}{}", env::consts::EXE_SUFFIX)))
+			.find(|p| is_executable(p))
+			.map(|path| Self { name: name.into(), path })
+	}
+
+	/// First line of the `--version` output, if the plugin supports the flag.
+	pub fn version(&self) -> Option<String> {
+		let output = Command::new(&self.path)
+			.arg("--version")
+			.stdin(Stdio::null())
+			.stderr(Stdio::null())
+			.output()
+			.ok()
+			.filter(|o| o.status.success())?;
+
+		String::from_utf8_lossy(&output.stdout).lines().next().map(|l| l.trim().into())
+	}
+
+	/// Run with `args`, forwarding the config file, the log filter and the color choice through
+	/// `CONFIG`, `LOG_FILTER` and `COLOR` behind [`config::env_prefix`], e.g. `MY_TOOL_CONFIG`.
+	///
+	/// A failing plugin reports its own error, its exit code is passed through as [`Exit`].
+	pub fn exec(&self, cx: &AppContext, global: &GlobalArgs, args: &[OsString]) -> Result<()> {
+		let prefix = config::env_prefix();
+		let mut cmd = Command::new(&self.path);
+
+		cmd.args(args)
+			.env(format!("{prefix}LOG_FILTER"), cx.config.log.filter.as_deref().unwrap_or("info"))
+			.env(
+				format!("{prefix}COLOR"),
+				global.color.to_possible_value().context("invalid color choice")?.get_name(),
+			);
+
+		if let Some(path) = config::resolve_path(global.config.as_deref())? {
+			cmd.env(format!("{prefix}CONFIG"), path);
+		}
+
+		tracing::debug!("running plugin `{}`", self.path.display());
+
+		let status = cmd
+			.status()
+			.wrap_err_with(|| format!("failed to run plugin `{}`", self.path.display()))?;
+
+		if status.success() {
+			return Ok(());
+		}
+
+		#[cfg(unix)]
+		if let Some(signal) = std::os::unix::process::ExitStatusExt::signal(&status) {
+			return Err(Exit(128 + signal as u8).into());
+		}
+
+		// Codes out of range, e.g. on Windows, must still report a failure.
+		Err(Exit(status.code().map_or(1, |c| c.clamp(1, u8::MAX.into()) as u8)).into())
+	}
+}
+
+/// Plugins in the plugins directory, then on `PATH`; the first one of each name shadows the
+/// others.
+pub fn discover(data_dir: &Path) -> Vec<Plugin> {
+	let mut plugins = Vec::<Plugin>::new();
+
+	for dir in search_path(data_dir) {
+		let Ok(entries) = fs::read_dir(&dir) else { continue };
+		let mut found = entries
+			.filter_map(|e| {
+				let path = e.ok()?.path();
+				let name = path
+					.file_name()?
+					.to_str()?
+					.strip_prefix(PREFIX)?
+					.strip_suffix(env::consts::EXE_SUFFIX)?
+					.to_owned();
+
+				Some(Plugin { name, path })
+			})
+			.filter(|p| {
+				!p.name.is_empty()
+					&& is_executable(&p.path)
+					&& !plugins.iter().any(|q| q.name == p.name)
+			})
+			.collect::<Vec<_>>();
+
+		found.sort_by(|a, b| a.name.cmp(&b.name));
+		plugins.append(&mut found);
+	}
+
+	plugins
+}
+
+/// Directory holding the plugins installed for this app only.
+pub fn dir(data_dir: &Path) -> PathBuf {
+	data_dir.join("plugins")
+}
+
+fn search_path(data_dir: &Path) -> Vec<PathBuf> {
+	iter::once(dir(data_dir)).chain(env::var_os("PATH").iter().flat_map(env::split_paths)).collect()
+}
+
+fn is_executable(path: &Path) -> bool {
+	#[cfg(unix)]
+	{
+		// std
+		use std::os::unix::fs::PermissionsExt;
+
+		path.metadata().is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+	}
+	#[cfg(not(unix))]
+	path.is_file()
+}
@@ -0,0 +1,36 @@
+// crates.io
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+// self
+#[cfg(feature = "async")] use crate::{config::RuntimeConfig, prelude::*};
+
+/// Async runtime scheduler.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum RuntimeFlavor {
+	/// Everything runs on the main thread.
+	CurrentThread,
+	/// Work-stealing pool of worker threads.
+	#[default]
+	MultiThread,
+}
+
+/// Build the async runtime `Cli::run` is driven by.
+#[cfg(feature = "async")]
+pub fn build(config: &RuntimeConfig) -> Result<tokio::runtime::Runtime> {
+	let mut builder = match config.flavor {
+		RuntimeFlavor::CurrentThread => tokio::runtime::Builder::new_current_thread(),
+		RuntimeFlavor::MultiThread => tokio::runtime::Builder::new_multi_thread(),
+	};
+
+	// Ignored by the current-thread scheduler.
+	if let Some(n) = config.worker_threads {
+		builder.worker_threads(n);
+	}
+
+	builder
+		.thread_name(env!("CARGO_PKG_NAME"))
+		.enable_all()
+		.build()
+		.wrap_err("failed to build the async runtime")
+}
@@ -0,0 +1,158 @@
+// std
+use std::{
+	process::ExitCode,
+	sync::{
+		atomic::{AtomicI32, Ordering},
+		Arc, Mutex,
+	},
+	time::Duration,
+};
+// crates.io
+use tracing_appender::non_blocking::WorkerGuard;
+// self
+use crate::prelude::*;
+
+/// Shutdown coordinator, a cheap to clone cancellation token.
+///
+/// The first `SIGINT` or `SIGTERM`, or Ctrl-C or Ctrl-Break on Windows, requests a shutdown:
+/// commands notice it through [`Shutdown::is_requested`] and have the grace period to wind down. A
+/// second signal, or the end of the grace period, flushes the logs and exits right away.
+#[derive(Clone, Debug, Default)]
+pub struct Shutdown(Arc<Inner>);
+#[derive(Debug, Default)]
+struct Inner {
+	// `0` until a signal is received.
+	signal: AtomicI32,
+	guard: Mutex<Option<WorkerGuard>>,
+	#[cfg(feature = "async")]
+	notify: tokio::sync::Notify,
+}
+impl Shutdown {
+	/// Keep the log writer guard, so it is flushed on every way out.
+	pub fn hold(&self, guard: Option<WorkerGuard>) {
+		*self.0.guard.lock().unwrap_or_else(|e| e.into_inner()) = guard;
+	}
+
+	/// Flush the log writer by dropping its guard.
+	pub fn flush(&self) {
+		drop(self.0.guard.lock().unwrap_or_else(|e| e.into_inner()).take());
+	}
+
+	/// Whether a shutdown has been requested.
+	pub fn is_requested(&self) -> bool {
+		self.signal().is_some()
+	}
+
+	/// Wait until a shutdown is requested.
+	#[cfg(feature = "async")]
+	pub async fn cancelled(&self) {
+		let notified = self.0.notify.notified();
+
+		if !self.is_requested() {
+			notified.await;
+		}
+	}
+
+	/// Conventional `128 + signal` exit code, if a signal requested the shutdown.
+	pub fn exit_code(&self) -> Option<ExitCode> {
+		self.signal().map(|s| ExitCode::from(128 + s as u8))
+	}
+
+	/// Handle `SIGINT` and `SIGTERM`, giving the process `grace` to exit by itself.
+	#[cfg(unix)]
+	pub fn install(&self, grace: Duration) -> Result<()> {
+		// crates.io
+		use signal_hook::{
+			consts::{SIGINT, SIGTERM},
+			iterator::Signals,
+		};
+
+		let mut signals = Signals::new([SIGINT, SIGTERM])?;
+		let shutdown = self.clone();
+
+		std::thread::spawn(move || {
+			for signal in signals.forever() {
+				shutdown.on_signal(signal, grace);
+			}
+		});
+
+		Ok(())
+	}
+
+	/// Handle Ctrl-C and Ctrl-Break like `SIGINT`, giving the process `grace` to exit by itself.
+	#[cfg(windows)]
+	pub fn install(&self, grace: Duration) -> Result<()> {
+		// std
+		use std::{io, sync::OnceLock};
+		// crates.io
+		use windows_sys::{
+			core::BOOL,
+			Win32::System::Console::{SetConsoleCtrlHandler, CTRL_BREAK_EVENT, CTRL_C_EVENT},
+		};
+
+		// The handler is a plain function, run by Windows on a thread of its own.
+		static INSTALLED: OnceLock<(Shutdown, Duration)> = OnceLock::new();
+
+		unsafe extern "system" fn handle(event: u32) -> BOOL {
+			match (event, INSTALLED.get()) {
+				(CTRL_C_EVENT | CTRL_BREAK_EVENT, Some((shutdown, grace))) => {
+					// `SIGINT`, for the exit code.
+					shutdown.on_signal(2, *grace);
+
+					1
+				},
+				// Closing the console or logging off still ends the process right away.
+				_ => 0,
+			}
+		}
+
+		if INSTALLED.set((self.clone(), grace)).is_err() {
+			bail!("the shutdown handler is already installed");
+		}
+		// SAFETY: `handle` only reads `INSTALLED`, which lives as long as the process.
+		if unsafe { SetConsoleCtrlHandler(Some(handle), 1) } == 0 {
+			return Err(io::Error::last_os_error().into());
+		}
+
+		Ok(())
+	}
+
+	fn on_signal(&self, signal: i32, grace: Duration) {
+		if self.is_requested() {
+			self.force_exit("received a second signal");
+		}
+
+		self.request(signal);
+
+		tracing::info!(
+			"received signal {signal}, shutting down within {grace:?}, send it again to force"
+		);
+
+		let shutdown = self.clone();
+
+		std::thread::spawn(move || {
+			std::thread::sleep(grace);
+			shutdown.force_exit("grace period elapsed");
+		});
+	}
+
+	fn signal(&self) -> Option<i32> {
+		Some(self.0.signal.load(Ordering::SeqCst)).filter(|s| *s != 0)
+	}
+
+	fn request(&self, signal: i32) {
+		self.0.signal.store(signal, Ordering::SeqCst);
+		#[cfg(feature = "async")]
+		self.0.notify.notify_waiters();
+	}
+
+	fn force_exit(&self, reason: &str) -> ! {
+		tracing::warn!("{reason}, exiting now");
+
+		self.flush();
+
+		let code = self.signal().map_or(Category::Interrupted.exit_code(), |s| 128 + s as u8);
+
+		std::process::exit(code.into());
+	}
+}
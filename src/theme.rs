@@ -0,0 +1,221 @@
+// std
+use std::path::Path;
+// crates.io
+use clap::builder::{
+	styling::{AnsiColor, Style},
+	Styles,
+};
+use serde::{Deserialize, Serialize};
+// self
+use crate::{
+	config::{Config, Overrides, ThemeConfig},
+	prelude::*,
+};
+
+/// Built-in theme.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ThemePreset {
+	#[default]
+	Default,
+	/// Bold and underline only, no colors.
+	Monochrome,
+	/// Bright colors, everything emphasized.
+	HighContrast,
+}
+
+/// Styles of the help output and of command output.
+#[derive(Clone, Copy, Debug)]
+pub struct Theme {
+	/// Section titles and table headers.
+	pub header: Style,
+	pub usage: Style,
+	/// Commands, flags and other literal text.
+	pub literal: Style,
+	/// Value names, e.g. `<PATH>`.
+	pub placeholder: Style,
+	pub error: Style,
+	/// Accepted values in usage errors.
+	pub valid: Style,
+	/// Rejected values in usage errors.
+	pub invalid: Style,
+}
+impl Theme {
+	/// Apply the overrides of `config` on top of its preset.
+	pub fn new(config: &ThemeConfig) -> Result<Self> {
+		let mut theme = Self::preset(config.preset);
+
+		for (style, spec) in [
+			(&mut theme.header, &config.header),
+			(&mut theme.usage, &config.usage),
+			(&mut theme.literal, &config.literal),
+			(&mut theme.placeholder, &config.placeholder),
+			(&mut theme.error, &config.error),
+			(&mut theme.valid, &config.valid),
+			(&mut theme.invalid, &config.invalid),
+		] {
+			if let Some(spec) = spec {
+				*style =
+					parse_style(spec).wrap_err_with(|| format!("invalid theme style `{spec}`"))?;
+			}
+		}
+
+		Ok(theme)
+	}
+
+	/// Theme of the config file at `path`, for the help output rendered before the configuration
+	/// is loaded.
+	///
+	/// Falls back to the default theme on any error, which loading the configuration will report.
+	pub fn preload(path: Option<&Path>) -> Self {
+		Config::figment(path, Overrides::default())
+			.and_then(|f| Ok(f.extract_inner::<ThemeConfig>("theme")?))
+			.and_then(|c| Self::new(&c))
+			.unwrap_or_default()
+	}
+
+	pub fn preset(preset: ThemePreset) -> Self {
+		match preset {
+			ThemePreset::Default => Self {
+				header: AnsiColor::Red.on_default().bold(),
+				usage: AnsiColor::Red.on_default().bold(),
+				literal: AnsiColor::Blue.on_default().bold(),
+				placeholder: AnsiColor::Green.on_default(),
+				error: AnsiColor::Red.on_default().bold(),
+				valid: AnsiColor::Green.on_default(),
+				invalid: AnsiColor::Yellow.on_default(),
+			},
+			ThemePreset::Monochrome => Self {
+				header: Style::new().bold(),
+				usage: Style::new().bold(),
+				literal: Style::new().bold(),
+				placeholder: Style::new().underline(),
+				error: Style::new().bold(),
+				valid: Style::new().underline(),
+				invalid: Style::new().bold(),
+			},
+			ThemePreset::HighContrast => Self {
+				header: AnsiColor::BrightYellow.on_default().bold().underline(),
+				usage: AnsiColor::BrightYellow.on_default().bold(),
+				literal: AnsiColor::BrightCyan.on_default().bold(),
+				placeholder: AnsiColor::BrightWhite.on_default().underline(),
+				error: AnsiColor::BrightRed.on_default().bold(),
+				valid: AnsiColor::BrightGreen.on_default().bold(),
+				invalid: AnsiColor::BrightMagenta.on_default().bold(),
+			},
+		}
+	}
+
+	/// Help and usage error styles.
+	pub fn styles(&self) -> Styles {
+		Styles::styled()
+			.header(self.header)
+			.usage(self.usage)
+			.literal(self.literal)
+			.placeholder(self.placeholder)
+			.error(self.error)
+			.valid(self.valid)
+			.invalid(self.invalid)
+	}
+}
+impl Default for Theme {
+	fn default() -> Self {
+		Self::preset(ThemePreset::default())
+	}
+}
+
+// Space separated words: `bold`, `dimmed`, `italic`, `underline`, a color, `on` followed by a
+// background color, or `none`.
+fn parse_style(spec: &str) -> Result<Style> {
+	let mut style = Style::new();
+	let mut words = spec.split_whitespace();
+
+	while let Some(word) = words.next() {
+		style = match word {
+			"none" => style,
+			"bold" => style.bold(),
+			"dimmed" => style.dimmed(),
+			"italic" => style.italic(),
+			"underline" => style.underline(),
+			"on" => style.bg_color(Some(
+				parse_color(words.next().context("expected a color after `on`")?)?.into(),
+			)),
+			w => style.fg_color(Some(parse_color(w)?.into())),
+		};
+	}
+
+	Ok(style)
+}
+
+fn parse_color(name: &str) -> Result<AnsiColor> {
+	Ok(match name {
+		"black" => AnsiColor::Black,
+		"red" => AnsiColor::Red,
+		"green" => AnsiColor::Green,
+		"yellow" => AnsiColor::Yellow,
+		"blue" => AnsiColor::Blue,
+		"magenta" => AnsiColor::Magenta,
+		"cyan" => AnsiColor::Cyan,
+		"white" => AnsiColor::White,
+		"bright-black" => AnsiColor::BrightBlack,
+		"bright-red" => AnsiColor::BrightRed,
+		"bright-green" => AnsiColor::BrightGreen,
+		"bright-yellow" => AnsiColor::BrightYellow,
+		"bright-blue" => AnsiColor::BrightBlue,
+		"bright-magenta" => AnsiColor::BrightMagenta,
+		"bright-cyan" => AnsiColor::BrightCyan,
+		"bright-white" => AnsiColor::BrightWhite,
+		_ => bail!("unknown color or effect `{name}`"),
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	// self
+	use super::*;
+
+	#[test]
+	fn parse_style_should_work() {
+		for (spec, expected) in [
+			("", Style::new()),
+			("none", Style::new()),
+			("bold underline", Style::new().bold().underline()),
+			("dimmed italic", Style::new().dimmed().italic()),
+			("red", AnsiColor::Red.on_default()),
+			("bright-cyan bold", AnsiColor::BrightCyan.on_default().bold()),
+			("on blue", Style::new().bg_color(Some(AnsiColor::Blue.into()))),
+			("yellow on bright-black", AnsiColor::Yellow.on(AnsiColor::BrightBlack)),
+			// The last color wins.
+			("red green", AnsiColor::Green.on_default()),
+		] {
+			assert_eq!(parse_style(spec).unwrap(), expected, "{spec}");
+		}
+		for (spec, expected) in [
+			("red on", "expected a color after `on`"),
+			("on bold", "unknown color or effect `bold`"),
+			("blink", "unknown color or effect `blink`"),
+			("bright-orange", "unknown color or effect `bright-orange`"),
+			("Red", "unknown color or effect `Red`"),
+		] {
+			assert_eq!(parse_style(spec).unwrap_err().to_string(), expected, "{spec}");
+		}
+	}
+
+	#[test]
+	fn new_should_apply_the_overrides_on_the_preset() {
+		let preset = Theme::preset(ThemePreset::HighContrast);
+		let theme = Theme::new(&ThemeConfig {
+			preset: ThemePreset::HighContrast,
+			error: Some("underline on red".into()),
+			..Default::default()
+		})
+		.unwrap();
+
+		assert_eq!(theme.error, Style::new().underline().bg_color(Some(AnsiColor::Red.into())));
+		assert_eq!(theme.header, preset.header);
+		assert_eq!(theme.invalid, preset.invalid);
+		assert!(
+			Theme::new(&ThemeConfig { usage: Some("blink".into()), ..Default::default() }).is_err()
+		);
+	}
+}
@@ -0,0 +1,342 @@
+mod notifier;
+pub use notifier::Notifier;
+
+// std
+use std::{
+	env, fs,
+	io::{Cursor, Read},
+	path::{Path, PathBuf},
+	process::{Command, Stdio},
+	time::Duration,
+};
+// crates.io
+use flate2::read::GzDecoder;
+use semver::Version;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use zip::{result::ZipError, ZipArchive};
+// self
+use crate::prelude::*;
+
+const NAME: &str = env!("CARGO_PKG_NAME");
+const TARGET: &str = env!("VERGEN_CARGO_TARGET_TRIPLE");
+
+/// Where releases are looked up.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Source {
+	/// GitHub releases of the package repository.
+	GitHub,
+	/// Directory or HTTP(S) URL holding a `latest` file with the tag of the latest release, and a
+	/// `<tag>/` directory per release with its assets and `SHA256` manifest.
+	Mirror(String),
+}
+impl Source {
+	/// Tag of the latest release, e.g. `v1.2.3`.
+	pub fn latest_tag(&self, timeout: Duration) -> Result<String> {
+		match self {
+			Self::GitHub => {
+				#[derive(Deserialize)]
+				struct Release {
+					tag_name: String,
+				}
+
+				let url = format!("https://api.github.com/repos/{}/releases/latest", repository());
+				let release = serde_json::from_slice::<Release>(&fetch(&url, timeout)?)
+					.wrap_err("unexpected response from the GitHub API")?;
+
+				Ok(release.tag_name)
+			},
+			Self::Mirror(base) =>
+				Ok(String::from_utf8(fetch(&format!("{base}/latest"), timeout)?)?.trim().into()),
+		}
+	}
+
+	/// Location of the asset `name` of release `tag`.
+	pub fn asset(&self, tag: &str, name: &str) -> String {
+		match self {
+			Self::GitHub =>
+				format!("https://github.com/{}/releases/download/{tag}/{name}", repository()),
+			Self::Mirror(base) => format!("{base}/{tag}/{name}"),
+		}
+	}
+}
+impl From<&str> for Source {
+	fn from(s: &str) -> Self {
+		match s {
+			"github" => Self::GitHub,
+			s => Self::Mirror(s.trim_end_matches('/').into()),
+		}
+	}
+}
+
+/// Version of release `tag`, with or without the `v` prefix.
+pub fn version(tag: &str) -> Result<Version> {
+	Version::parse(tag.strip_prefix('v').unwrap_or(tag))
+		.wrap_err_with(|| format!("`{tag}` is not a version tag"))
+}
+
+/// Download the archive built for this target from release `tag`, verified against the release
+/// `SHA256` manifest.
+///
+/// Returns the asset name along with its content.
+pub fn download(source: &Source, tag: &str, timeout: Duration) -> Result<(String, Vec<u8>)> {
+	let manifest = String::from_utf8(fetch(&source.asset(tag, "SHA256"), timeout)?)?;
+	let prefix = format!("{NAME}-{TARGET}.");
+	// `sha256sum` format, `*` marking binary mode.
+	let (expected, name) = manifest
+		.lines()
+		.filter_map(|l| l.split_once(char::is_whitespace))
+		.map(|(hash, name)| (hash, name.trim_start().trim_start_matches('*')))
+		.find(|(_, name)| name.starts_with(&prefix))
+		.with_context(|| format!("release `{tag}` has no asset for `{TARGET}`"))?;
+	let archive = fetch(&source.asset(tag, name), timeout)?;
+	let actual = format!("{:x}", Sha256::digest(&archive));
+
+	if !actual.eq_ignore_ascii_case(expected) {
+		bail!("checksum mismatch for `{name}`, expected `{expected}` but got `{actual}`");
+	}
+
+	Ok((name.into(), archive))
+}
+
+/// Extract the executable from the release archive `name`.
+pub fn extract(name: &str, archive: &[u8]) -> Result<Vec<u8>> {
+	let exe = format!("{NAME}{}", env::consts::EXE_SUFFIX);
+	let mut binary = Vec::new();
+
+	if name.ends_with(".tar.gz") {
+		let mut tar = tar::Archive::new(GzDecoder::new(archive));
+
+		for entry in tar.entries()? {
+			let mut entry = entry?;
+
+			if entry.path()?.to_str() == Some(&exe) {
+				entry.read_to_end(&mut binary)?;
+
+				return Ok(binary);
+			}
+		}
+	} else if name.ends_with(".zip") {
+		let mut zip = ZipArchive::new(Cursor::new(archive))?;
+
+		match zip.by_name(&exe) {
+			Ok(mut file) => {
+				file.read_to_end(&mut binary)?;
+
+				return Ok(binary);
+			},
+			Err(ZipError::FileNotFound) => (),
+			Err(e) => Err(e)?,
+		};
+	} else {
+		bail!("unsupported archive `{name}`");
+	}
+
+	bail!("`{name}` does not contain `{exe}`")
+}
+
+/// Replace the running executable with `binary`, keeping the current one if anything fails.
+///
+/// The new executable is written next to the current one and smoke tested first, then swapped in
+/// with renames, which are atomic on the same filesystem.
+pub fn replace_exe(binary: &[u8]) -> Result<PathBuf> {
+	let exe = env::current_exe()?.canonicalize()?;
+	let file_name = exe.file_name().and_then(|n| n.to_str()).context("invalid executable path")?;
+	let staged = exe.with_file_name(format!(".{file_name}.new"));
+
+	fs::write(&staged, binary)
+		.wrap_err_with(|| format!("failed to write `{}`", staged.display()))?;
+	fs::set_permissions(&staged, fs::metadata(&exe)?.permissions())?;
+
+	match Command::new(&staged).arg("--version").stdout(Stdio::null()).status() {
+		Ok(status) if status.success() => (),
+		r => {
+			let _ = fs::remove_file(&staged);
+
+			bail!(
+				"the downloaded executable does not run, {}",
+				r.map_or_else(|e| e.to_string(), |s| s.to_string())
+			);
+		},
+	}
+
+	if let Err(e) = swap(&staged, &exe) {
+		let _ = fs::remove_file(&staged);
+
+		return Err(e);
+	}
+
+	Ok(exe)
+}
+
+// The running executable stays open while its path is renamed over, so there is always one there.
+#[cfg(unix)]
+fn swap(staged: &Path, exe: &Path) -> Result<()> {
+	fs::rename(staged, exe).wrap_err_with(|| format!("failed to replace `{}`", exe.display()))
+}
+
+// A running executable cannot be replaced on Windows, only moved out of the way.
+#[cfg(not(unix))]
+fn swap(staged: &Path, exe: &Path) -> Result<()> {
+	let file_name = exe.file_name().and_then(|n| n.to_str()).context("invalid executable path")?;
+	let backup = exe.with_file_name(format!(".{file_name}.old"));
+
+	// Left over by a previous update, as a running executable cannot be deleted either.
+	let _ = fs::remove_file(&backup);
+
+	fs::rename(exe, &backup).wrap_err_with(|| format!("failed to move `{}`", exe.display()))?;
+
+	if let Err(e) = fs::rename(staged, exe) {
+		// Roll back.
+		fs::rename(&backup, exe)
+			.wrap_err_with(|| format!("failed to restore `{}`", exe.display()))?;
+
+		return Err(e).wrap_err_with(|| format!("failed to replace `{}`", exe.display()));
+	}
+
+	let _ = fs::remove_file(&backup);
+
+	Ok(())
+}
+
+/// Read `location`, an HTTP(S) URL or a local path.
+pub fn fetch(location: &str, timeout: Duration) -> Result<Vec<u8>> {
+	if !location.starts_with("http://") && !location.starts_with("https://") {
+		let path = location.strip_prefix("file://").unwrap_or(location);
+
+		return fs::read(path).wrap_err_with(|| format!("failed to read `{path}`"));
+	}
+
+	let agent =
+		ureq::Agent::from(ureq::Agent::config_builder().timeout_global(Some(timeout)).build());
+
+	agent
+		.get(location)
+		.header("User-Agent", concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION")))
+		.call()
+		.and_then(|mut r| r.body_mut().with_config().limit(u64::MAX).read_to_vec())
+		.wrap_err_with(|| format!("failed to fetch `{location}`"))
+		.category(Category::Network)
+}
+
+// `owner/repo` of `CARGO_PKG_REPOSITORY`.
+fn repository() -> &'static str {
+	env!("CARGO_PKG_REPOSITORY").trim_start_matches("https://github.com/").trim_end_matches('/')
+}
+
+#[cfg(test)]
+mod tests {
+	// std
+	use std::{io::Write, process};
+	// crates.io
+	use flate2::{write::GzEncoder, Compression};
+	use zip::{write::SimpleFileOptions, ZipWriter};
+	// self
+	use super::*;
+
+	const TIMEOUT: Duration = Duration::from_secs(5);
+
+	fn temp_dir(name: &str) -> PathBuf {
+		let dir = env::temp_dir().join(format!("update-{name}-{}", process::id()));
+
+		let _ = fs::remove_dir_all(&dir);
+
+		fs::create_dir_all(&dir).unwrap();
+
+		dir
+	}
+
+	fn exe() -> String {
+		format!("{NAME}{}", env::consts::EXE_SUFFIX)
+	}
+
+	fn tar_gz(files: &[(&str, &[u8])]) -> Vec<u8> {
+		let mut tar = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+
+		for (path, content) in files {
+			let mut header = tar::Header::new_gnu();
+
+			header.set_size(content.len() as u64);
+			header.set_mode(0o755);
+			tar.append_data(&mut header, path, *content).unwrap();
+		}
+
+		tar.into_inner().unwrap().finish().unwrap()
+	}
+
+	fn zip(files: &[(&str, &[u8])]) -> Vec<u8> {
+		let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+
+		for (path, content) in files {
+			zip.start_file(*path, SimpleFileOptions::default()).unwrap();
+			zip.write_all(content).unwrap();
+		}
+
+		zip.finish().unwrap().into_inner()
+	}
+
+	#[test]
+	fn download_should_verify_the_checksum() {
+		let mirror = temp_dir("mirror");
+		let source = Source::from(mirror.to_str().unwrap());
+		let name = format!("{NAME}-{TARGET}.tar.gz");
+		let archive = b"archive";
+		let release = mirror.join("v1.0.0");
+
+		fs::create_dir_all(&release).unwrap();
+		fs::write(release.join(&name), archive).unwrap();
+		fs::write(
+			release.join("SHA256"),
+			format!(
+				"{:x}  {NAME}-other-target.tar.gz\n{:X} *{name}\n",
+				Sha256::digest(b"other"),
+				Sha256::digest(archive),
+			),
+		)
+		.unwrap();
+
+		assert_eq!(download(&source, "v1.0.0", TIMEOUT).unwrap(), (name.clone(), archive.to_vec()));
+
+		fs::write(release.join(&name), b"tampered").unwrap();
+
+		assert!(download(&source, "v1.0.0", TIMEOUT)
+			.unwrap_err()
+			.to_string()
+			.starts_with(&format!("checksum mismatch for `{name}`")));
+
+		fs::write(release.join("SHA256"), format!("{:x}  other.zip\n", Sha256::digest(b"other")))
+			.unwrap();
+
+		assert_eq!(
+			download(&source, "v1.0.0", TIMEOUT).unwrap_err().to_string(),
+			format!("release `v1.0.0` has no asset for `{TARGET}`")
+		);
+		assert!(download(&source, "v2.0.0", TIMEOUT).is_err());
+
+		fs::remove_dir_all(mirror).unwrap();
+	}
+
+	#[test]
+	fn extract_should_find_the_executable() {
+		let exe = exe();
+		let files = [("README.md", b"readme".as_slice()), (exe.as_str(), b"binary".as_slice())];
+
+		assert_eq!(extract("a.tar.gz", &tar_gz(&files)).unwrap(), b"binary");
+		assert_eq!(extract("a.zip", &zip(&files)).unwrap(), b"binary");
+	}
+
+	#[test]
+	fn extract_should_fail_without_the_executable() {
+		let files = [("README.md", b"readme".as_slice())];
+
+		assert_eq!(
+			extract("a.tar.gz", &tar_gz(&files)).unwrap_err().to_string(),
+			format!("`a.tar.gz` does not contain `{}`", exe())
+		);
+		assert_eq!(
+			extract("a.zip", &zip(&files)).unwrap_err().to_string(),
+			format!("`a.zip` does not contain `{}`", exe())
+		);
+		assert_eq!(extract("a.7z", b"").unwrap_err().to_string(), "unsupported archive `a.7z`");
+	}
+}
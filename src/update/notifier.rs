@@ -0,0 +1,233 @@
+// std
+use std::{
+	env, fs,
+	path::Path,
+	thread::{self, JoinHandle},
+	time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+// crates.io
+use semver::Version;
+use serde::{Deserialize, Serialize};
+// self
+use crate::{
+	context::AppContext,
+	update::{self, Source},
+};
+
+const CACHE_FILE: &str = "update-check.json";
+const TIMEOUT: Duration = Duration::from_secs(5);
+// Time the finished command gives a pending check before exiting without it.
+const GRACE: Duration = Duration::from_millis(500);
+
+/// Update-available notice, checked in the background while the command runs.
+///
+/// The latest release is looked up at most once per `update.check_interval_hours`, the result
+/// being cached in the data directory.
+#[derive(Debug, Default)]
+pub struct Notifier {
+	latest: Option<Version>,
+	pending: Option<JoinHandle<Option<Version>>>,
+}
+impl Notifier {
+	/// Start a check, unless disabled by `update.check`, in CI, outside of a terminal, or if the
+	/// cached result is recent enough.
+	pub fn spawn(cx: &AppContext) -> Self {
+		let config = &cx.config.update;
+
+		if !config.check
+			|| env::var_os("CI").is_some_and(|v| !v.is_empty())
+			|| !cx.term.is_interactive()
+		{
+			return Self::default();
+		}
+
+		let path = cx.dirs.data.join(CACHE_FILE);
+		let interval = config.check_interval_hours.saturating_mul(60 * 60);
+
+		match plan(Cache::read(&path), &config.source, interval, now()) {
+			Plan::Cached(latest) => Self { latest, pending: None },
+			Plan::Check(marker) => {
+				marker.write(&path);
+
+				Self { latest: None, pending: Some(thread::spawn(move || check(marker, &path))) }
+			},
+		}
+	}
+
+	/// Print a one-line notice if a newer release is known, waiting briefly for a pending check.
+	pub fn finish(self) {
+		let latest = match self.pending {
+			Some(pending) => {
+				let deadline = Instant::now() + GRACE;
+
+				while !pending.is_finished() && Instant::now() < deadline {
+					thread::sleep(Duration::from_millis(10));
+				}
+
+				if pending.is_finished() {
+					pending.join().ok().flatten()
+				} else {
+					None
+				}
+			},
+			None => self.latest,
+		};
+		let Ok(current) = Version::parse(env!("CARGO_PKG_VERSION")) else { return };
+
+		if let Some(latest) = latest.filter(|l| *l > current) {
+			eprintln!(
+				"{name} {latest} is available, {current} is installed, run `{name} self-update` to \
+				update",
+				name = env!("CARGO_PKG_NAME"),
+			);
+		}
+	}
+}
+
+#[derive(Debug, PartialEq, Eq, Deserialize, Serialize)]
+struct Cache {
+	source: String,
+	// Seconds since the Unix epoch.
+	checked_at: u64,
+	latest: Option<Version>,
+}
+impl Cache {
+	fn read(path: &Path) -> Option<Self> {
+		serde_json::from_slice(&fs::read(path).ok()?).ok()
+	}
+
+	fn write(&self, path: &Path) {
+		if let Err(e) =
+			serde_json::to_vec(self).map_err(Into::into).and_then(|c| fs::write(path, c))
+		{
+			tracing::debug!("failed to write `{}`: {e}", path.display());
+		}
+	}
+}
+
+// What to do about the cached check.
+#[derive(Debug, PartialEq, Eq)]
+enum Plan {
+	// Recent enough, use the version it found.
+	Cached(Option<Version>),
+	// Check again, recording this first.
+	Check(Cache),
+}
+
+// A check stays valid for `interval` seconds, `now` being seconds since the Unix epoch.
+fn plan(cache: Option<Cache>, source: &str, interval: u64, now: u64) -> Plan {
+	// A result for another source is meaningless.
+	match cache.filter(|c| c.source == source) {
+		Some(cache) if now.saturating_sub(cache.checked_at) < interval =>
+			Plan::Cached(cache.latest),
+		// Recorded upfront, so that a slow or unreachable source, whose check fails or outlives the
+		// command, is not retried on every run. The last known version stays until one succeeds.
+		cache => Plan::Check(Cache {
+			source: source.into(),
+			checked_at: now,
+			latest: cache.and_then(|c| c.latest),
+		}),
+	}
+}
+
+// Look up the latest release of the source of `marker`, the cache recorded before, and cache it
+// at `path`.
+fn check(marker: Cache, path: &Path) -> Option<Version> {
+	match Source::from(marker.source.as_str()).latest_tag(TIMEOUT).and_then(|t| update::version(&t))
+	{
+		Ok(latest) => {
+			Cache { checked_at: now(), latest: Some(latest.clone()), ..marker }.write(path);
+
+			Some(latest)
+		},
+		Err(e) => {
+			tracing::debug!("update check failed: {e:?}");
+
+			marker.latest
+		},
+	}
+}
+
+fn now() -> u64 {
+	SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+	// std
+	use std::process;
+	// self
+	use super::*;
+
+	const HOUR: u64 = 60 * 60;
+
+	fn cache(source: &str, checked_at: u64, latest: Option<&str>) -> Cache {
+		Cache {
+			source: source.into(),
+			checked_at,
+			latest: latest.map(|v| Version::parse(v).unwrap()),
+		}
+	}
+
+	#[test]
+	fn plan_should_work() {
+		let now = 100 * HOUR;
+
+		for (cached, expected) in [
+			(None, Plan::Check(cache("github", now, None))),
+			// Within the interval.
+			(
+				Some(cache("github", now - HOUR, Some("1.2.3"))),
+				Plan::Cached(Some(Version::new(1, 2, 3))),
+			),
+			(Some(cache("github", now - HOUR, None)), Plan::Cached(None)),
+			// A clock set back is not a reason to check.
+			(Some(cache("github", now + HOUR, None)), Plan::Cached(None)),
+			// Expired, the last known version is kept in the recorded marker.
+			(
+				Some(cache("github", now - 24 * HOUR, Some("1.2.3"))),
+				Plan::Check(cache("github", now, Some("1.2.3"))),
+			),
+			// Another source invalidates the cache.
+			(
+				Some(cache("https://example.com", now - HOUR, Some("1.2.3"))),
+				Plan::Check(cache("github", now, None)),
+			),
+		] {
+			assert_eq!(plan(cached, "github", 24 * HOUR, now), expected);
+		}
+	}
+
+	#[test]
+	fn check_should_keep_the_last_known_version_on_failure() {
+		let dir = env::temp_dir().join(format!("notifier-{}", process::id()));
+		let path = dir.join(CACHE_FILE);
+
+		let _ = fs::remove_dir_all(&dir);
+
+		fs::create_dir_all(&dir).unwrap();
+
+		// An unreachable source leaves the marker as recorded.
+		let source = dir.join("missing").display().to_string();
+		let marker = cache(&source, 1, Some("1.2.3"));
+
+		marker.write(&path);
+
+		assert_eq!(check(marker, &path), Some(Version::new(1, 2, 3)));
+		assert_eq!(Cache::read(&path), Some(cache(&source, 1, Some("1.2.3"))));
+
+		// A successful check replaces it.
+		let source = dir.display().to_string();
+
+		fs::write(dir.join("latest"), "v2.0.0\n").unwrap();
+
+		assert_eq!(check(cache(&source, 1, Some("1.2.3")), &path), Some(Version::new(2, 0, 0)));
+
+		let cache = Cache::read(&path).unwrap();
+
+		assert_eq!(cache.latest, Some(Version::new(2, 0, 0)));
+		assert!(cache.checked_at > 1);
+
+		fs::remove_dir_all(dir).unwrap();
+	}
+}
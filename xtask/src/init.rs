@@ -0,0 +1,370 @@
+// std
+use std::{
+	fs,
+	path::{Path, PathBuf},
+};
+// crates.io
+use clap::Args;
+// self
+use crate::prelude::*;
+
+// Assembled at compile time so that this file survives its own rewrite.
+const NAME: &str = concat!("<", "NAME", ">");
+const DESCRIPTION: &str = concat!("<", "DESCRIPTION", ">");
+// GitHub repository of the template, and the application author derived from its owner.
+const REPOSITORY: &str = concat!("hack-ink/", "<", "NAME", ">");
+const APP_AUTHOR: &str = "author: \"hack.ink\"";
+const SKIPPED_DIRS: &[&str] = &[".git", "target"];
+const RESERVED_NAMES: &[&str] = &[
+	"alloc",
+	"aux",
+	"con",
+	"core",
+	"crate",
+	"nul",
+	"prn",
+	"proc_macro",
+	"self",
+	"std",
+	"super",
+	"test",
+];
+
+/// Instantiate the template by rewriting its placeholders.
+///
+/// Lives outside of the template crate, which cannot be built before its placeholders are
+/// replaced.
+#[derive(Debug, Args)]
+pub struct InitCmd {
+	/// Crate name of the new project.
+	#[arg(long, value_name = "NAME", value_parser = parse_crate_name)]
+	name: String,
+	/// One-line description of the new project.
+	#[arg(long, value_name = "TEXT", value_parser = parse_description)]
+	description: String,
+	/// Author recorded in `Cargo.toml`, e.g. `Jane Doe <jane@example.com>`.
+	#[arg(long, value_name = "AUTHOR")]
+	author: Option<String>,
+	/// Homepage recorded in `Cargo.toml`.
+	#[arg(long, value_name = "URL")]
+	homepage: Option<String>,
+	/// GitHub repository of the new project, used by the manifest, the badges, `self-update` and
+	/// the issue links; its owner becomes the author of the application directories.
+	#[arg(long, value_name = "OWNER/NAME", value_parser = parse_repository)]
+	repository: Option<String>,
+	/// Root of the template tree.
+	#[arg(long, value_name = "PATH", default_value = ".")]
+	path: PathBuf,
+	/// Print the summary without writing anything.
+	#[arg(long)]
+	dry_run: bool,
+}
+impl InitCmd {
+	pub fn run(&self) -> Result<()> {
+		let mut files = Vec::new();
+
+		collect_files(&self.path, &mut files)?;
+		files.sort();
+
+		let mut changed = 0;
+		let mut replaced = 0;
+
+		for file in files {
+			// Binary files are never templated.
+			let Ok(old) = fs::read_to_string(&file) else { continue };
+			let (new, n) = self.rewrite(&file, &old);
+
+			if new == old {
+				continue;
+			}
+
+			println!(
+				"{} ({n} replacement(s))",
+				file.strip_prefix(&self.path).unwrap_or(&file).display()
+			);
+
+			for (o, n) in old.lines().zip(new.lines()).filter(|(o, n)| o != n) {
+				println!("  - {o}");
+				println!("  + {n}");
+			}
+
+			if !self.dry_run {
+				fs::write(&file, new)
+					.wrap_err_with(|| format!("failed to write `{}`", file.display()))?;
+			}
+
+			changed += 1;
+			replaced += n;
+		}
+
+		if changed == 0 {
+			bail!("no placeholders found under `{}`", self.path.display());
+		}
+
+		println!(
+			"{changed} file(s) changed, {replaced} replacement(s){}",
+			if self.dry_run { " (dry run)" } else { "" }
+		);
+
+		Ok(())
+	}
+
+	fn rewrite(&self, file: &Path, content: &str) -> (String, usize) {
+		// Only the template manifest, not the one of this tool.
+		let is_manifest = file == self.path.join("Cargo.toml");
+		let description =
+			if is_manifest { toml_escape(&self.description) } else { self.description.clone() };
+		let owner = self.repository.as_deref().and_then(|r| r.split_once('/')).map(|(o, _)| o);
+		// The repository goes first, as it holds the name placeholder.
+		let replacements = [
+			(REPOSITORY, self.repository.clone()),
+			(APP_AUTHOR, owner.map(|o| format!("author: \"{o}\""))),
+			(NAME, Some(self.name.clone())),
+			(DESCRIPTION, Some(description)),
+		];
+		let replace = |s: &str| {
+			replacements.iter().fold((s.to_owned(), 0), |(s, n), (from, to)| match to {
+				Some(to) => (s.replace(from, to), n + s.matches(from).count()),
+				None => (s, n),
+			})
+		};
+
+		if !is_manifest {
+			return replace(content);
+		}
+
+		let authors = self.author.as_deref().map(|a| format!("[\"{}\"]", toml_escape(a)));
+		let homepage = self.homepage.as_deref().map(|h| format!("\"{}\"", toml_escape(h)));
+		let mut n = 0;
+		let new = content
+			.lines()
+			.map(|l| {
+				let (l, m) = replace(l);
+
+				// A value set from the options is one replacement, whatever placeholders it held.
+				match set_value(&l, "authors", authors.as_deref())
+					.or_else(|| set_value(&l, "homepage", homepage.as_deref()))
+				{
+					Some(v) if v != l => {
+						n += 1;
+
+						v
+					},
+					_ => {
+						n += m;
+
+						l
+					},
+				}
+			})
+			.collect::<Vec<_>>()
+			.join("\n")
+			+ "\n";
+
+		(new, n)
+	}
+}
+
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+	for entry in
+		fs::read_dir(dir).wrap_err_with(|| format!("failed to read `{}`", dir.display()))?
+	{
+		let entry = entry?;
+		let path = entry.path();
+
+		if entry.file_type()?.is_dir() {
+			if !SKIPPED_DIRS.iter().any(|d| entry.file_name() == *d) {
+				collect_files(&path, files)?;
+			}
+		} else {
+			files.push(path);
+		}
+	}
+
+	Ok(())
+}
+
+fn set_value(line: &str, key: &str, value: Option<&str>) -> Option<String> {
+	let value = value?;
+	let (k, _) = line.split_once('=')?;
+
+	(k.trim() == key).then(|| format!("{k}= {value}"))
+}
+
+fn toml_escape(s: &str) -> String {
+	s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn parse_crate_name(s: &str) -> Result<String> {
+	if s.is_empty() || s.len() > 64 {
+		bail!("crate name must be between 1 and 64 characters");
+	}
+	if !s.starts_with(|c: char| c.is_ascii_alphabetic()) {
+		bail!("crate name must start with an ASCII letter");
+	}
+	if let Some(c) = s.chars().find(|c| !c.is_ascii_alphanumeric() && *c != '-' && *c != '_') {
+		bail!("invalid character `{c}` in crate name");
+	}
+	if RESERVED_NAMES.contains(&s.to_ascii_lowercase().replace('-', "_").as_str()) {
+		bail!("`{s}` is a reserved name");
+	}
+
+	Ok(s.into())
+}
+
+fn parse_repository(s: &str) -> Result<String> {
+	let valid = |part: &str| {
+		!part.is_empty()
+			&& part.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+	};
+
+	match s.split_once('/') {
+		Some((owner, name)) if valid(owner) && valid(name) => Ok(s.into()),
+		_ => bail!("repository must be `OWNER/NAME`, e.g. `jane/my-tool`"),
+	}
+}
+
+fn parse_description(s: &str) -> Result<String> {
+	if s.trim().is_empty() || s.contains('\n') {
+		bail!("description must be a single non-empty line");
+	}
+
+	Ok(s.into())
+}
+
+#[cfg(test)]
+mod tests {
+	// std
+	use std::path::PathBuf;
+	// self
+	use super::*;
+
+	fn cmd(author: Option<&str>, homepage: Option<&str>) -> InitCmd {
+		InitCmd {
+			name: "my-tool".into(),
+			description: r#"A "quoted" \ tool"#.into(),
+			author: author.map(Into::into),
+			homepage: homepage.map(Into::into),
+			repository: None,
+			path: PathBuf::from("root"),
+			dry_run: false,
+		}
+	}
+
+	#[test]
+	fn parse_crate_name_should_work() {
+		for ok in ["a", "my-tool", "my_tool", "x1", &"a".repeat(64)] {
+			assert_eq!(parse_crate_name(ok).unwrap(), ok);
+		}
+		for err in ["", "1tool", "-tool", "my tool", "my.tool", "tööl", "std", "Self", "proc-macro"]
+		{
+			assert!(parse_crate_name(err).is_err(), "{err}");
+		}
+
+		assert!(parse_crate_name(&"a".repeat(65)).is_err());
+	}
+
+	#[test]
+	fn parse_repository_should_work() {
+		for ok in ["jane/my-tool", "hack-ink/my_tool.rs"] {
+			assert_eq!(parse_repository(ok).unwrap(), ok);
+		}
+		for err in ["", "my-tool", "/my-tool", "jane/", "jane/my/tool", "https://github.com/jane/x"]
+		{
+			assert!(parse_repository(err).is_err(), "{err}");
+		}
+	}
+
+	#[test]
+	fn rewrite_manifest_should_work() {
+		let manifest = format!(
+			"[package]\n\
+			authors     = [\"Xavier Lau <x@acg.box>\"]\n\
+			description = \"{DESCRIPTION}\"\n\
+			homepage    = \"https://hack.ink/{NAME}\"\n\
+			name        = \"{NAME}\"\n"
+		);
+		let (new, n) = cmd(Some(r#"Jane "JD" Doe"#), Some("https://example.com"))
+			.rewrite(&PathBuf::from("root/Cargo.toml"), &manifest);
+
+		assert_eq!(
+			new,
+			"[package]\n\
+			authors     = [\"Jane \\\"JD\\\" Doe\"]\n\
+			description = \"A \\\"quoted\\\" \\\\ tool\"\n\
+			homepage    = \"https://example.com\"\n\
+			name        = \"my-tool\"\n"
+		);
+		assert_eq!(n, 4);
+	}
+
+	#[test]
+	fn rewrite_repository_should_work() {
+		let init = InitCmd { repository: Some("jane/tools".into()), ..cmd(None, None) };
+
+		for (file, content, expected, count) in [
+			(
+				"root/Cargo.toml",
+				format!(
+					"name       = \"{NAME}\"\nrepository = \"https://github.com/{REPOSITORY}\"\n"
+				),
+				"name       = \"my-tool\"\nrepository = \"https://github.com/jane/tools\"\n",
+				2,
+			),
+			(
+				"root/README.md",
+				format!("[![Checks]({REPOSITORY}/badge.svg)](https://github.com/{REPOSITORY})"),
+				"[![Checks](jane/tools/badge.svg)](https://github.com/jane/tools)",
+				2,
+			),
+			(
+				"root/src/main.rs",
+				format!("AppInfo {{ name: \"{NAME}\", {APP_AUTHOR} }}"),
+				"AppInfo { name: \"my-tool\", author: \"jane\" }",
+				2,
+			),
+		] {
+			let (new, n) = init.rewrite(&PathBuf::from(file), &content);
+
+			assert_eq!((new.as_str(), n), (expected, count), "{file}");
+		}
+
+		// Without the option, only the name changes.
+		assert_eq!(
+			cmd(None, None)
+				.rewrite(&PathBuf::from("root/src/main.rs"), &format!("{REPOSITORY} {APP_AUTHOR}")),
+			(format!("hack-ink/my-tool {APP_AUTHOR}"), 1)
+		);
+	}
+
+	#[test]
+	fn rewrite_manifest_without_metadata_should_keep_it() {
+		let manifest = format!("authors = [\"Xavier Lau <x@acg.box>\"]\nname = \"{NAME}\"\n");
+		let (new, n) = cmd(None, None).rewrite(&PathBuf::from("root/Cargo.toml"), &manifest);
+
+		assert_eq!(new, "authors = [\"Xavier Lau <x@acg.box>\"]\nname = \"my-tool\"\n");
+		assert_eq!(n, 1);
+	}
+
+	#[test]
+	fn rewrite_other_files_should_not_escape() {
+		let (new, n) = cmd(Some("Jane"), None).rewrite(
+			&PathBuf::from("root/README.md"),
+			&format!("# {NAME}\n### {DESCRIPTION}\nauthors = x\n"),
+		);
+
+		assert_eq!(new, "# my-tool\n### A \"quoted\" \\ tool\nauthors = x\n");
+		assert_eq!(n, 2);
+	}
+
+	#[test]
+	fn rewrite_nested_manifest_should_only_replace_placeholders() {
+		let (new, n) = cmd(Some("Jane"), None).rewrite(
+			&PathBuf::from("root/xtask/Cargo.toml"),
+			&format!("authors = [\"x\"]\nname = \"{NAME}\"\n"),
+		);
+
+		assert_eq!(new, "authors = [\"x\"]\nname = \"my-tool\"\n");
+		assert_eq!(n, 1);
+	}
+}
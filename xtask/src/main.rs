@@ -0,0 +1,34 @@
+//! Template maintenance tasks, run with `cargo run --manifest-path xtask/Cargo.toml -- <TASK>`.
+
+#![deny(clippy::all, missing_docs, unused_crate_dependencies)]
+
+mod init;
+use init::InitCmd;
+
+mod prelude {
+	pub use color_eyre::eyre::{bail, Result, WrapErr};
+}
+use prelude::*;
+
+// crates.io
+use clap::{Parser, Subcommand};
+
+#[derive(Debug, Parser)]
+#[command(rename_all = "kebab")]
+struct Xtask {
+	#[command(subcommand)]
+	task: Task,
+}
+
+#[derive(Debug, Subcommand)]
+enum Task {
+	Init(InitCmd),
+}
+
+fn main() -> Result<()> {
+	color_eyre::install()?;
+
+	match Xtask::parse().task {
+		Task::Init(cmd) => cmd.run(),
+	}
+}